@@ -0,0 +1,83 @@
+//! Times eval on functions with many segments, for each layout of the segments and each storage.
+//! Run with `cargo bench`.
+extern crate partial_function;
+
+use partial_function::*;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const EVALS: usize = 200_000;
+
+/// Returns the average time taken by eval over EVALS values spread across the segments.
+fn time<O>(segments: usize, eval: impl Fn(i64) -> Option<O>) -> Duration {
+    let start = Instant::now();
+    for i in 0..EVALS {
+        let x = (i * 7919 % (segments * 10)) as i64;
+        black_box(eval(black_box(x)));
+    }
+    start.elapsed() / EVALS as u32
+}
+
+/// Builds a function of segments [i*10, i*10+10[.
+fn build(segments: usize) -> PartialFunction<'static, i64, i64> {
+    (0..segments as i64)
+        .map(|i| {
+            let (l, h) = (i * 10, i * 10 + 10);
+            let f: Box<dyn Fn(i64) -> i64> = Box::new(move |x| x - l);
+            (l, h, f)
+        })
+        .collect::<PartialFunctionBuilder<_, _>>()
+        .build()
+}
+
+/// Compares scanning segments stored as an array of structures, like PartialFunction did
+/// before 0.6, with scanning bounds stored apart from the functions, and with eval.
+fn layouts() {
+    let segments = 4000;
+    let aos = build(segments).into_iter().collect::<Vec<_>>();
+    let (mut lowers, mut highers, mut funcs) = (vec![], vec![], vec![]);
+    for b in build(segments) {
+        lowers.push(b.lower);
+        highers.push(b.higher);
+        funcs.push(b.func);
+    }
+    let func = build(segments);
+    println!(
+        "{} segments: scan of structures {:?}, scan of bound vectors {:?}, eval {:?} per eval",
+        segments,
+        time(segments, |x| aos
+            .iter()
+            .find(|b| b.lower <= x && x < b.higher)
+            .map(|b| (b.func)(x))),
+        time(segments, |x| lowers
+            .iter()
+            .zip(&highers)
+            .position(|(&l, &h)| l <= x && x < h)
+            .map(|i| (funcs[i])(x))),
+        time(segments, |x| func.eval(x)),
+    );
+}
+
+/// Compares PartialFunction with OrderedPartialFunction.
+fn storages() {
+    for &segments in [1000, 10_000].iter() {
+        let vec = build(segments);
+        let ordered = (0..segments as i64)
+            .map(|i| (i * 10, i * 10 + 10))
+            .fold(OrderedPartialFunction::new(), |p, (l, h)| {
+                p.with(l, h, Box::new(move |x| x - l))
+            })
+            .build();
+        println!(
+            "{} segments: PartialFunction {:?}, OrderedPartialFunction {:?} per eval",
+            segments,
+            time(segments, |x| vec.eval(x)),
+            time(segments, |x| ordered.eval(x)),
+        );
+    }
+}
+
+fn main() {
+    layouts();
+    storages();
+}
@@ -0,0 +1,146 @@
+use crate::{
+    in_segment, is_undefined, overlaps, reorder, InsertError, NaNBoundError, OverlapError,
+};
+use std::cmp::Ordering;
+use std::fmt::Debug;
+
+/// Bounds of the segments of a function keeping what its segments hold in a separate vector,
+/// following the rules of the segments of a PartialFunction.
+///
+/// Builders add bounds in insertion order and sort them when building, which gives the order
+/// to apply to what the segments hold.
+pub(crate) struct Bounds<B> {
+    /// Lower bound of each segment, sorted once built.
+    lowers: Vec<B>,
+    /// Higher bound of each segment, aligned with lowers.
+    highers: Vec<B>,
+}
+
+impl<B> Bounds<B> {
+    /// Creates bounds without any segment.
+    pub fn new() -> Self {
+        Bounds {
+            lowers: vec![],
+            highers: vec![],
+        }
+    }
+}
+
+impl<B: PartialOrd> Bounds<B> {
+    /// Check if a segment can be added, like PartialFunctionBuilder::can_insert.
+    pub fn can_insert(&self, lower: &B, higher: &B) -> bool {
+        !is_undefined(lower)
+            && !is_undefined(higher)
+            && lower <= higher
+            && self.overlapping(lower, higher).is_none()
+    }
+
+    /// Finds the first segment overlapping the bounds.
+    fn overlapping(&self, lower: &B, higher: &B) -> Option<usize> {
+        self.lowers
+            .iter()
+            .zip(&self.highers)
+            .position(|(l, h)| overlaps(lower, higher, l, h))
+    }
+
+    /// Adds a segment, panicking in debug builds if it can't be added, like
+    /// PartialFunctionBuilder::with.
+    pub fn push(&mut self, lower: B, higher: B)
+    where
+        B: Debug,
+    {
+        debug_assert!(
+            !is_undefined(&lower) && !is_undefined(&higher),
+            "bounds of a segment can't be NaN"
+        );
+        debug_assert!(
+            lower <= higher,
+            "segment [{:?}, {:?}) has inverted bounds, lower can't be above higher",
+            lower,
+            higher
+        );
+        if cfg!(debug_assertions) {
+            if let Some(i) = self.overlapping(&lower, &higher) {
+                panic!(
+                    "segment [{:?}, {:?}) overlaps segment {} [{:?}, {:?})",
+                    lower, higher, i, self.lowers[i], self.highers[i]
+                );
+            }
+        }
+        self.lowers.push(lower);
+        self.highers.push(higher);
+    }
+
+    /// Adds a segment, returning an error instead if it can't be added, like
+    /// PartialFunctionBuilder::try_with.
+    pub fn try_push(&mut self, lower: B, higher: B) -> Result<(), InsertError<B>>
+    where
+        B: Clone,
+    {
+        if is_undefined(&lower) || is_undefined(&higher) {
+            return Err(InsertError::NaNBound(NaNBoundError));
+        }
+        if lower > higher {
+            return Err(InsertError::InvertedBounds { lower, higher });
+        }
+        if let Some(first) = self.overlapping(&lower, &higher) {
+            let existing = &self.lowers[first];
+            let at = if existing > &lower {
+                existing.clone()
+            } else {
+                lower
+            };
+            return Err(InsertError::Overlap(OverlapError {
+                first,
+                second: self.lowers.len(),
+                at,
+            }));
+        }
+        self.lowers.push(lower);
+        self.highers.push(higher);
+        Ok(())
+    }
+
+    /// Sorts the segments by bounds, returning the insertion index of each sorted segment.
+    pub fn sort(&mut self) -> Vec<usize> {
+        let mut order = (0..self.lowers.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| {
+            self.lowers[a]
+                .partial_cmp(&self.lowers[b])
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| {
+                    self.highers[a]
+                        .partial_cmp(&self.highers[b])
+                        .unwrap_or(Ordering::Equal)
+                })
+        });
+        self.lowers = reorder(std::mem::take(&mut self.lowers), order.iter().copied());
+        self.highers = reorder(std::mem::take(&mut self.highers), order.iter().copied());
+        order
+    }
+
+    /// Finds the index of the sorted segment containing x, the highest bound of the last
+    /// segment being included.
+    pub fn index_of(&self, x: &B) -> Option<usize> {
+        if is_undefined(x) {
+            return None;
+        }
+        // Segments don't overlap, so only the last one starting at or before x can contain it.
+        let i = self
+            .lowers
+            .partition_point(|lower| lower <= x)
+            .checked_sub(1)?;
+        let is_last = i + 1 == self.lowers.len();
+        if in_segment(
+            &self.lowers[i],
+            &self.highers[i],
+            is_last,
+            x,
+            &B::partial_cmp,
+        ) {
+            Some(i)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use crate::{shared, PartialFunction, Unbounded};
+use std::rc::Rc;
+
+/// A segment function shared between clones.
+type SharedFn<'a, B, O> = Rc<Box<dyn Fn(B) -> O + 'a>>;
+
+/// A partial function sharing its functions between clones.
+/// See PartialFunction::cloneable.
+pub struct CloneablePartialFunction<'a, B, O> {
+    func: PartialFunction<'a, B, O>,
+    /// Functions of the segments, aligned with them.
+    funcs: Vec<SharedFn<'a, B, O>>,
+    below: Option<SharedFn<'a, B, O>>,
+    above: Option<SharedFn<'a, B, O>>,
+}
+
+impl<'a, B: 'a, O: 'a> PartialFunction<'a, B, O> {
+    /// Makes the function cloneable, the clones sharing the functions of the segments.
+    pub fn cloneable(mut self) -> CloneablePartialFunction<'a, B, O> {
+        let funcs = self.funcs.drain(..).map(Rc::new).collect::<Vec<_>>();
+        self.funcs = funcs.iter().map(shared).collect();
+        let share = |u: Unbounded<'a, B, O>| {
+            let f = Rc::new(u.func);
+            let u = Unbounded {
+                func: shared(&f),
+                bound: u.bound,
+            };
+            (u, f)
+        };
+        let below;
+        let above;
+        (self.below, below) = self.below.take().map(share).unzip();
+        (self.above, above) = self.above.take().map(share).unzip();
+        CloneablePartialFunction {
+            func: self,
+            funcs,
+            below,
+            above,
+        }
+    }
+}
+
+impl<'a, B: PartialOrd, O> CloneablePartialFunction<'a, B, O> {
+    /// Evaluates the partial function.
+    /// Returns None if no function is defined.
+    pub fn eval(&self, x: B) -> Option<O> {
+        self.func.eval(x)
+    }
+
+    /// Returns the partial function.
+    pub fn partial(&self) -> &PartialFunction<'a, B, O> {
+        &self.func
+    }
+
+    /// Returns the partial function, to transform it. Its functions stay shared with the clones.
+    pub fn into_partial(self) -> PartialFunction<'a, B, O> {
+        self.func
+    }
+}
+
+impl<'a, B: Clone + 'a, O: 'a> Clone for CloneablePartialFunction<'a, B, O> {
+    fn clone(&self) -> Self {
+        let unbounded = |u: &Option<Unbounded<'a, B, O>>, f: &Option<SharedFn<'a, B, O>>| {
+            u.as_ref().zip(f.as_ref()).map(|(u, f)| Unbounded {
+                func: shared(f),
+                bound: u.bound.clone(),
+            })
+        };
+        let func = PartialFunction {
+            lowers: self.func.lowers.clone(),
+            highers: self.func.highers.clone(),
+            funcs: self.funcs.iter().map(shared).collect(),
+            breakpoints: self.func.breakpoints.clone(),
+            end: self.func.end.clone(),
+            precedence: self.func.precedence.clone(),
+            half_open: self.func.half_open,
+            open_lowest: self.func.open_lowest,
+            below: unbounded(&self.func.below, &self.below),
+            above: unbounded(&self.func.above, &self.above),
+        };
+        CloneablePartialFunction {
+            func,
+            funcs: self.funcs.clone(),
+            below: self.below.clone(),
+            above: self.above.clone(),
+        }
+    }
+}
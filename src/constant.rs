@@ -0,0 +1,83 @@
+use crate::bounds::Bounds;
+use crate::reorder;
+use std::fmt::Debug;
+
+/// A partial function whose segments each hold a constant value instead of a function.
+/// Evaluating it returns a reference to the stored value, which avoids calling a function and
+/// copying its output, making it a cheap lookup table for big values.
+///
+/// Segments follow the same rules as the ones of a PartialFunction.
+pub struct PartialConstFunction<B, O> {
+    /// Bounds of the segments, sorted.
+    bounds: Bounds<B>,
+    /// Value of each segment, aligned with the bounds.
+    values: Vec<O>,
+}
+
+impl<B: PartialOrd, O> PartialConstFunction<B, O> {
+    /// Creates a new PartialConstFunctionBuilder.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> PartialConstFunctionBuilder<B, O> {
+        PartialConstFunctionBuilder {
+            bounds: Bounds::new(),
+            values: vec![],
+        }
+    }
+
+    /// Returns a reference to the value of the segment containing x.
+    /// Returns None if no segment contains x.
+    pub fn eval_ref(&self, x: &B) -> Option<&O> {
+        let i = self.bounds.index_of(x)?;
+        Some(&self.values[i])
+    }
+}
+
+/// A PartialConstFunction holding references to values owned elsewhere, such as configuration
+/// objects, which can be looked up without cloning them.
+pub type PartialRefFunction<'a, B, O> = PartialConstFunction<B, &'a O>;
+
+impl<'a, B: PartialOrd, O> PartialConstFunction<B, &'a O> {
+    /// Returns the reference held by the segment containing x, borrowed for as long as the
+    /// referenced value lives rather than for as long as the function.
+    /// Returns None if no segment contains x.
+    pub fn eval(&self, x: B) -> Option<&'a O> {
+        self.eval_ref(&x).copied()
+    }
+}
+
+/// A builder to create an immutable PartialConstFunction.
+pub struct PartialConstFunctionBuilder<B, O> {
+    /// Bounds of the segments in insertion order.
+    bounds: Bounds<B>,
+    /// Values in insertion order.
+    values: Vec<O>,
+}
+
+impl<B: PartialOrd, O> PartialConstFunctionBuilder<B, O> {
+    /// Adds a segment bounded between [lower,higher[ holding value.
+    pub fn with(mut self, lower: B, higher: B, value: O) -> Self
+    where
+        B: Debug,
+    {
+        self.bounds.push(lower, higher);
+        self.values.push(value);
+        self
+    }
+
+    /// Check if you can safely insert into the function list for the specified bounds.
+    pub fn can_insert(&self, lower: &B, higher: &B) -> bool {
+        self.bounds.can_insert(lower, higher)
+    }
+}
+
+impl<B: PartialOrd, O> PartialConstFunctionBuilder<B, O> {
+    /// Builds the PartialConstFunction from the values added using with.
+    pub fn build(mut self) -> PartialConstFunction<B, O> {
+        let order = self.bounds.sort();
+        let values = reorder(self.values, order.into_iter());
+        PartialConstFunction {
+            bounds: self.bounds,
+            values,
+        }
+    }
+}
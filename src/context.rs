@@ -0,0 +1,148 @@
+use crate::bounds::Bounds;
+use crate::{is_undefined, reorder};
+use std::cmp::Ordering;
+use std::fmt::Debug;
+
+/// A segment function also receiving a context when evaluated.
+type ContextFn<'a, B, C, O> = Box<dyn Fn(B, &mut C) -> O + 'a>;
+
+/// A partial function whose segment functions receive a mutable context when evaluated,
+/// for functions needing external state without capturing it.
+///
+/// Segments follow the same rules as the ones of a PartialFunction.
+pub struct ContextPartialFunction<'a, B, C, O> {
+    /// Bounds of the segments, sorted.
+    bounds: Bounds<B>,
+    /// Function of each segment, aligned with the bounds.
+    funcs: Vec<ContextFn<'a, B, C, O>>,
+}
+
+impl<'a, B: PartialOrd, C, O> ContextPartialFunction<'a, B, C, O> {
+    /// Creates a new ContextPartialFunctionBuilder.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> ContextPartialFunctionBuilder<'a, B, C, O> {
+        ContextPartialFunctionBuilder {
+            bounds: Bounds::new(),
+            funcs: vec![],
+        }
+    }
+
+    /// Evaluates the partial function, passing ctx to the function of the segment.
+    /// Returns None if no function is defined.
+    pub fn eval(&self, x: B, ctx: &mut C) -> Option<O> {
+        let i = self.bounds.index_of(&x)?;
+        Some((self.funcs[i])(x, ctx))
+    }
+}
+
+/// A builder to create an immutable ContextPartialFunction.
+pub struct ContextPartialFunctionBuilder<'a, B, C, O> {
+    /// Bounds of the segments in insertion order.
+    bounds: Bounds<B>,
+    /// Functions in insertion order.
+    funcs: Vec<ContextFn<'a, B, C, O>>,
+}
+
+impl<'a, B: PartialOrd, C, O> ContextPartialFunctionBuilder<'a, B, C, O> {
+    /// Adds a bounded function bounded between [lower,higher[ of function func.
+    pub fn with(mut self, lower: B, higher: B, func: ContextFn<'a, B, C, O>) -> Self
+    where
+        B: Debug,
+    {
+        self.bounds.push(lower, higher);
+        self.funcs.push(func);
+        self
+    }
+
+    /// Check if you can safely insert into the function list for the specified bounds.
+    pub fn can_insert(&self, lower: &B, higher: &B) -> bool {
+        self.bounds.can_insert(lower, higher)
+    }
+}
+
+impl<'a, B: PartialOrd, C, O> ContextPartialFunctionBuilder<'a, B, C, O> {
+    /// Builds the ContextPartialFunction from the functions added using with.
+    pub fn build(mut self) -> ContextPartialFunction<'a, B, C, O> {
+        let order = self.bounds.sort();
+        let funcs = reorder(self.funcs, order.into_iter());
+        ContextPartialFunction {
+            bounds: self.bounds,
+            funcs,
+        }
+    }
+}
+
+/// A lower partial function whose segment functions receive a mutable context when evaluated.
+///
+/// Segments follow the same rules as the ones of a LowerPartialFunction.
+pub struct ContextLowerPartialFunction<'a, B: PartialOrd, C, O> {
+    /// Lower bound of each segment, sorted.
+    lowers: Vec<B>,
+    /// Function of each segment, aligned with lowers.
+    funcs: Vec<ContextFn<'a, B, C, O>>,
+}
+
+impl<'a, B: PartialOrd, C, O> ContextLowerPartialFunction<'a, B, C, O> {
+    /// Creates a new ContextLowerPartialFunctionBuilder.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> ContextLowerPartialFunctionBuilder<'a, B, C, O> {
+        ContextLowerPartialFunctionBuilder {
+            lowers: vec![],
+            funcs: vec![],
+        }
+    }
+
+    /// Evaluates the partial function, passing ctx to the function of the segment.
+    /// Returns None if no function is defined for the searched invariable value (x).
+    pub fn eval(&self, x: B, ctx: &mut C) -> Option<O> {
+        if is_undefined(&x) {
+            return None;
+        }
+        // Each segment lasts until the next lower bound, so the last one starting at or before
+        // x contains it.
+        let i = self
+            .lowers
+            .partition_point(|lower| lower <= &x)
+            .checked_sub(1)?;
+        Some((self.funcs[i])(x, ctx))
+    }
+}
+
+/// A builder to create an immutable ContextLowerPartialFunction.
+pub struct ContextLowerPartialFunctionBuilder<'a, B, C, O> {
+    /// Lower bounds in insertion order.
+    lowers: Vec<B>,
+    /// Functions in insertion order.
+    funcs: Vec<ContextFn<'a, B, C, O>>,
+}
+
+impl<'a, B: PartialOrd, C, O> ContextLowerPartialFunctionBuilder<'a, B, C, O> {
+    /// Adds a bounded function starting at lower, until the next segment.
+    pub fn with(mut self, lower: B, func: ContextFn<'a, B, C, O>) -> Self {
+        debug_assert!(!is_undefined(&lower), "bound of a segment can't be NaN");
+        debug_assert!(self.can_insert(&lower));
+        self.lowers.push(lower);
+        self.funcs.push(func);
+        self
+    }
+
+    /// Check if you can safely insert into the function list for the specified bound.
+    pub fn can_insert(&self, lower: &B) -> bool {
+        !is_undefined(lower) && !self.lowers.contains(lower)
+    }
+}
+
+impl<'a, B: PartialOrd, C, O> ContextLowerPartialFunctionBuilder<'a, B, C, O> {
+    /// Builds the ContextLowerPartialFunction from the functions added using with.
+    pub fn build(self) -> ContextLowerPartialFunction<'a, B, C, O> {
+        let mut order = (0..self.lowers.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| {
+            self.lowers[a]
+                .partial_cmp(&self.lowers[b])
+                .unwrap_or(Ordering::Equal)
+        });
+        let lowers = reorder(self.lowers, order.iter().copied());
+        let funcs = reorder(self.funcs, order.into_iter());
+        ContextLowerPartialFunction { lowers, funcs }
+    }
+}
@@ -0,0 +1,110 @@
+use crate::{ConversionError, DualBoundedFunction, LowerPartialFunction, PartialFunction};
+
+impl<'a, B: PartialOrd + Clone, O, L> LowerPartialFunction<'a, B, O, L> {
+    /// Converts to a PartialFunction where each segment ends where the next one starts,
+    /// the last one ending at final_upper.
+    /// The labels are dropped, as are segments hidden by a segment of higher priority.
+    pub fn into_bounded(self, final_upper: B) -> PartialFunction<'a, B, O> {
+        debug_assert!(self.funcs.last().is_none_or(|b| b.lower <= final_upper));
+        let highers = self
+            .funcs
+            .iter()
+            .skip(1)
+            .map(|b| b.lower.clone())
+            .collect::<Vec<_>>();
+        // The segments are already sorted and can only share a lower bound through priorities,
+        // the last one being used.
+        let segments = self
+            .funcs
+            .into_iter()
+            .zip(highers.into_iter().map(Some).chain(std::iter::once(None)))
+            .filter(|(b, next)| next.as_ref() != Some(&b.lower))
+            .map(|(b, next)| {
+                let higher = next.unwrap_or_else(|| final_upper.clone());
+                DualBoundedFunction::new(b.func, b.lower, higher)
+            })
+            .collect();
+        PartialFunction::from_sorted_segments_unchecked(segments)
+    }
+}
+
+impl<'a, B: PartialOrd + Clone, O> PartialFunction<'a, B, O> {
+    /// Converts to a LowerPartialFunction, which requires each segment to end where the next
+    /// one starts.
+    ///
+    /// The higher bound of the last segment is dropped, so the resulting function stays
+    /// defined up to infinity. A segment unbounded above is kept as the last segment when it
+    /// starts where the finite segments end.
+    /// Returns an error on the first gap, or if the function is unbounded below.
+    pub fn into_lower(self) -> Result<LowerPartialFunction<'a, B, O>, ConversionError> {
+        if self.below.is_some() {
+            return Err(ConversionError::UnboundedBelow);
+        }
+        if let Some(i) = self
+            .highers
+            .iter()
+            .zip(self.lowers.iter().skip(1))
+            .position(|(higher, next)| higher != next)
+        {
+            return Err(ConversionError::Gap { segment: i });
+        }
+        if let (Some(last), Some(above)) = (self.highers.last(), &self.above) {
+            if last != &above.bound {
+                return Err(ConversionError::Gap {
+                    segment: self.funcs.len() - 1,
+                });
+            }
+        }
+        let segments = self.lowers.into_iter().zip(self.funcs);
+        let above = self.above.map(|u| (u.bound, u.func));
+        Ok(LowerPartialFunction::new()
+            .extend(segments.chain(above))
+            .build())
+    }
+}
+
+impl<'a, B: PartialOrd + 'a, O: 'a> PartialFunction<'a, B, O> {
+    /// Turns the function into a closure evaluating it.
+    pub fn into_fn(self) -> impl Fn(B) -> Option<O> + 'a {
+        move |x| self.eval(x)
+    }
+
+    /// Turns the function into a closure evaluating it, returning default where it is undefined.
+    pub fn into_total_fn(self, default: O) -> impl Fn(B) -> O + 'a
+    where
+        O: Clone,
+    {
+        move |x| self.eval(x).unwrap_or_else(|| default.clone())
+    }
+}
+
+impl<'a, B: PartialOrd + 'a, O: 'a> From<PartialFunction<'a, B, O>>
+    for Box<dyn Fn(B) -> Option<O> + 'a>
+{
+    fn from(func: PartialFunction<'a, B, O>) -> Self {
+        Box::new(func.into_fn())
+    }
+}
+
+impl<'a, B: PartialOrd + 'a, O: 'a, L: 'a> LowerPartialFunction<'a, B, O, L> {
+    /// Turns the function into a closure evaluating it.
+    pub fn into_fn(self) -> impl Fn(B) -> Option<O> + 'a {
+        move |x| self.eval(x)
+    }
+
+    /// Turns the function into a closure evaluating it, returning default where it is undefined.
+    pub fn into_total_fn(self, default: O) -> impl Fn(B) -> O + 'a
+    where
+        O: Clone,
+    {
+        move |x| self.eval(x).unwrap_or_else(|| default.clone())
+    }
+}
+
+impl<'a, B: PartialOrd + 'a, O: 'a, L: 'a> From<LowerPartialFunction<'a, B, O, L>>
+    for Box<dyn Fn(B) -> Option<O> + 'a>
+{
+    fn from(func: LowerPartialFunction<'a, B, O, L>) -> Self {
+        Box::new(func.into_fn())
+    }
+}
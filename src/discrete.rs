@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A partial function defined only at specific keys, each with its own function.
+/// Useful for keys without an order, like enum variants or strings, where segments make no sense.
+pub struct DiscretePartialFunction<'a, B: Eq + Hash, O> {
+    funcs: HashMap<B, Box<dyn Fn(B) -> O + 'a>>,
+}
+
+impl<'a, B: Eq + Hash, O> DiscretePartialFunction<'a, B, O> {
+    /// Creates a new DiscretePartialFunctionBuilder.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> DiscretePartialFunctionBuilder<'a, B, O> {
+        DiscretePartialFunctionBuilder::new()
+    }
+
+    /// Evaluates the partial function.
+    /// Returns None if no function is defined for x.
+    pub fn eval(&self, x: B) -> Option<O> {
+        let f = self.funcs.get(&x)?;
+        Some(f(x))
+    }
+}
+
+/// A builder to create an immutable DiscretePartialFunction.
+#[derive(new)]
+pub struct DiscretePartialFunctionBuilder<'a, B: Eq + Hash, O> {
+    #[new(default)]
+    funcs: HashMap<B, Box<dyn Fn(B) -> O + 'a>>,
+}
+
+impl<'a, B: Eq + Hash, O> DiscretePartialFunctionBuilder<'a, B, O> {
+    /// Adds a function defined only at key.
+    pub fn with(mut self, key: B, func: Box<dyn Fn(B) -> O + 'a>) -> Self {
+        debug_assert!(self.can_insert(&key));
+        self.funcs.insert(key, func);
+        self
+    }
+
+    /// Check if you can safely insert a function at key, which is the case if no function is
+    /// defined there yet.
+    pub fn can_insert(&self, key: &B) -> bool {
+        !self.funcs.contains_key(key)
+    }
+
+    /// Builds the DiscretePartialFunction from the functions added using with.
+    pub fn build(self) -> DiscretePartialFunction<'a, B, O> {
+        DiscretePartialFunction { funcs: self.funcs }
+    }
+}
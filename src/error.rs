@@ -0,0 +1,197 @@
+use std::error::Error;
+use std::fmt;
+
+/// Returned when two segments of a builder overlap.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverlapError<B> {
+    /// Insertion index of the segment inserted first.
+    pub first: usize,
+    /// Insertion index of the segment inserted last.
+    pub second: usize,
+    /// A value contained by both segments.
+    pub at: B,
+}
+
+impl<B: fmt::Debug> fmt::Display for OverlapError<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "segments {} and {} overlap at {:?}",
+            self.first, self.second, self.at
+        )
+    }
+}
+
+impl<B: fmt::Debug> Error for OverlapError<B> {}
+
+/// Returned when a bound can't be compared, like a NaN float.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NaNBoundError;
+
+impl fmt::Display for NaNBoundError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "bound is NaN and can't be compared")
+    }
+}
+
+impl Error for NaNBoundError {}
+
+/// Returned when a segment can't be inserted into a builder.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InsertError<B> {
+    /// The segment overlaps an already inserted segment.
+    Overlap(OverlapError<B>),
+    /// A bound can't be compared, like a NaN float.
+    NaNBound(NaNBoundError),
+    /// The segment overlaps a function going to infinity.
+    OverlapUnbounded {
+        /// The lower bound of the rejected segment.
+        lower: B,
+        /// The higher bound of the rejected segment.
+        higher: B,
+    },
+    /// The lower bound is greater than the higher bound.
+    InvertedBounds {
+        /// The lower bound of the rejected segment.
+        lower: B,
+        /// The higher bound of the rejected segment.
+        higher: B,
+    },
+}
+
+impl<B: fmt::Debug> fmt::Display for InsertError<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InsertError::Overlap(e) => e.fmt(f),
+            InsertError::NaNBound(e) => e.fmt(f),
+            InsertError::OverlapUnbounded { lower, higher } => write!(
+                f,
+                "segment [{:?}, {:?}] overlaps an unbounded segment",
+                lower, higher
+            ),
+            InsertError::InvertedBounds { lower, higher } => {
+                write!(
+                    f,
+                    "lower bound {:?} is above higher bound {:?}",
+                    lower, higher
+                )
+            }
+        }
+    }
+}
+
+impl<B: fmt::Debug> Error for InsertError<B> {}
+
+/// Returned when several segments can't be inserted into a builder at once.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuildError<B> {
+    /// Every problem found.
+    pub errors: Vec<InsertError<B>>,
+}
+
+impl<B: fmt::Debug> fmt::Display for BuildError<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} segments can't be inserted", self.errors.len())?;
+        if let Some(first) = self.errors.first() {
+            write!(f, ", first: {}", first)?;
+        }
+        Ok(())
+    }
+}
+
+impl<B: fmt::Debug> Error for BuildError<B> {}
+
+/// Returned when a function is evaluated where it isn't defined, telling why.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvalError<B> {
+    /// The value is below the lowest bound of the function.
+    BelowDomain {
+        /// The lowest bound of the function.
+        min: B,
+    },
+    /// The value is above the highest bound of the function.
+    AboveDomain {
+        /// The highest bound of the function.
+        max: B,
+    },
+    /// The value is between two segments.
+    InGap {
+        /// The higher bound of the segment before the gap.
+        gap_start: B,
+        /// The lower bound of the segment after the gap.
+        gap_end: B,
+    },
+    /// The function has no segment.
+    Empty,
+    /// The value can't be compared, like a NaN float.
+    NaN,
+    /// The value can't be compared with the bounds of the function, which happens with
+    /// partially ordered bounds.
+    Incomparable,
+}
+
+impl<B: fmt::Debug> fmt::Display for EvalError<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EvalError::BelowDomain { min } => {
+                write!(f, "value is below the domain starting at {:?}", min)
+            }
+            EvalError::AboveDomain { max } => {
+                write!(f, "value is above the domain ending at {:?}", max)
+            }
+            EvalError::InGap { gap_start, gap_end } => write!(
+                f,
+                "value is in the gap between {:?} and {:?}",
+                gap_start, gap_end
+            ),
+            EvalError::Empty => write!(f, "function has no segment"),
+            EvalError::NaN => write!(f, "value is NaN and can't be compared"),
+            EvalError::Incomparable => {
+                write!(f, "value can't be compared with the bounds of the function")
+            }
+        }
+    }
+}
+
+impl<B: fmt::Debug> Error for EvalError<B> {}
+
+/// Returned when a PartialFunction can't be converted to a LowerPartialFunction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The segment at this index doesn't end where the next one starts.
+    Gap {
+        /// Index of the segment, sorted by lower bound.
+        segment: usize,
+    },
+    /// The function is defined down to -infinity.
+    UnboundedBelow,
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConversionError::Gap { segment } => {
+                write!(f, "segment {} doesn't touch the next segment", segment)
+            }
+            ConversionError::UnboundedBelow => write!(f, "function is unbounded below"),
+        }
+    }
+}
+
+impl Error for ConversionError {}
+
+/// Returned when a function can't be scaled by a factor, like a lower partial function
+/// scaled by a factor which isn't strictly positive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScaleError<B> {
+    /// The rejected factor.
+    pub factor: B,
+}
+
+impl<B: fmt::Debug> fmt::Display for ScaleError<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cannot scale by factor {:?}", self.factor)
+    }
+}
+
+impl<B: fmt::Debug> Error for ScaleError<B> {}
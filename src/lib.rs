@@ -2,98 +2,1518 @@
 extern crate derive_new;
 
 use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::iter::FromIterator;
+use std::ops::Sub;
+use std::rc::Rc;
+
+mod bounds;
+mod cloneable;
+mod constant;
+mod context;
+mod convert;
+mod discrete;
+mod error;
+mod linear;
+mod mapped;
+#[cfg(feature = "memoize")]
+mod memoize;
+mod numeric;
+mod ordered;
+mod periodic;
+mod plot;
+mod poly;
+mod profile;
+mod segment;
+mod surface;
+mod sync;
+mod total;
+mod transform;
+mod zip;
+
+pub use crate::cloneable::CloneablePartialFunction;
+pub use crate::constant::{PartialConstFunction, PartialConstFunctionBuilder, PartialRefFunction};
+pub use crate::context::{
+    ContextLowerPartialFunction, ContextLowerPartialFunctionBuilder, ContextPartialFunction,
+    ContextPartialFunctionBuilder,
+};
+pub use crate::discrete::{DiscretePartialFunction, DiscretePartialFunctionBuilder};
+pub use crate::error::{
+    BuildError, ConversionError, EvalError, InsertError, NaNBoundError, OverlapError, ScaleError,
+};
+pub use crate::linear::NonMonotonicError;
+pub use crate::mapped::MappedPartialFunction;
+#[cfg(feature = "memoize")]
+pub use crate::memoize::{Memoized, MemoizedPartialFunction};
+pub use crate::numeric::Real;
+pub use crate::ordered::{OrderedPartialFunction, OrderedPartialFunctionBuilder};
+pub use crate::periodic::PeriodicPartialFunction;
+pub use crate::poly::{PolyPartialFunction, PolyPartialFunctionBuilder, PolySegment};
+pub use crate::profile::ProfiledPartialFunction;
+pub use crate::segment::{SegmentFn, SegmentPartialFunction, SegmentPartialFunctionBuilder};
+pub use crate::surface::{
+    PartialFunction2, PartialFunction2Builder, PartialFunction2D, PartialFunction2DBuilder,
+};
+pub use crate::sync::{SyncPartialFunction, SyncPartialFunctionBuilder};
+pub use crate::total::TotalPartialFunction;
+pub use crate::zip::ZippedPartialFunction;
+
+/// Checks if a value can't be compared, even to itself, like a NaN float.
+fn is_undefined<B: PartialOrd>(x: &B) -> bool {
+    x.partial_cmp(x).is_none()
+}
+
+/// Reorders items given in insertion order, indices giving the insertion index of each item
+/// in the new order.
+pub(crate) fn reorder<T>(items: Vec<T>, indices: impl Iterator<Item = usize>) -> Vec<T> {
+    let mut items = items.into_iter().map(Some).collect::<Vec<_>>();
+    indices
+        .map(|i| items[i].take().expect("item used twice"))
+        .collect()
+}
+
+/// Wraps a shared function so it can be stored in a segment.
+fn shared<'a, B: 'a, O: 'a>(f: &Rc<Box<dyn Fn(B) -> O + 'a>>) -> Box<dyn Fn(B) -> O + 'a> {
+    let f = Rc::clone(f);
+    Box::new(move |x| f(x))
+}
 
 /// A regular function that is only defined between lower and higher.
 /// If two functions intersect their higher and lower bounds respectively.
 /// The second will take precedence where f(lower).
 #[derive(new)]
-pub struct DualBoundedFunction<B, O> {
+pub struct DualBoundedFunction<'a, B, O> {
     /// The stored function f(x) = ???
-    pub func: Box<dyn Fn(B) -> O>,
+    pub func: Box<dyn Fn(B) -> O + 'a>,
     /// The lower bound of the function.
     pub lower: B,
     /// The higher bound of the function.
     pub higher: B,
 }
 
+impl<'a, B: PartialOrd, O> DualBoundedFunction<'a, B, O> {
+    /// Checks if the segment is defined at a single value, its lower and higher bounds being equal.
+    fn is_point(&self) -> bool {
+        self.lower == self.higher
+    }
+}
+
 /// Define a functions defined by multiple functions parts.
 /// See BoundedFunction.
-/// Uses bounds as [lower,higher],
-/// except in the case of a lower bound overlapping a higher bound.
-/// In this case, the lower bound always take precedence.
-pub struct PartialFunction<B, O> {
-    funcs: Vec<DualBoundedFunction<B, O>>,
+/// Uses bounds as `[lower,higher[`, except for the segment with the highest bound of the
+/// whole function, which uses `[lower,higher]`.
+///
+/// Where segments touch, the value they share is always evaluated by the segment starting
+/// at that value, whatever the order they were added in.
+/// For the segments `[0,1]`, `[1,2]` and `[2,3]`, 1 is evaluated by `[1,2]`, 2 by `[2,3]`
+/// and 3 by `[2,3]` since it is the end of the function.
+///
+/// Segments with equal bounds are points, defined only at that value.
+/// A point can't share its value with the lower bound of another segment, which already
+/// contains it, but can be placed on the excluded higher bound of a segment.
+///
+/// Values that can't be compared, like NaN floats, are never accepted as bounds
+/// and evaluating at such a value always returns None.
+///
+/// The functions of the segments can borrow data living for 'a,
+/// which is inferred as 'static for functions that don't borrow anything.
+/// Before 0.6 the type had no lifetime parameter: write PartialFunction<'static, B, O> instead.
+pub struct PartialFunction<'a, B, O> {
+    /// Lower bound of each segment, sorted.
+    /// Bounds are kept apart from the functions so searching a segment only reads bounds.
+    lowers: Vec<B>,
+    /// Higher bound of each segment, aligned with lowers.
+    highers: Vec<B>,
+    /// Function of each segment, aligned with lowers.
+    funcs: Vec<Box<dyn Fn(B) -> O + 'a>>,
+    /// Sorted and deduplicated bounds of all segments, computed when building.
+    breakpoints: Vec<B>,
+    /// Highest higher bound of the segments, which the segments ending there include unless a
+    /// point starts there. Computed with the breakpoints.
+    end: Option<B>,
+    /// Priority and insertion index of each segment, aligned with funcs, when segments
+    /// can overlap and the greatest one takes precedence. Empty otherwise.
+    precedence: Vec<(i32, usize)>,
+    /// Whether the last segment excludes its higher bound, like all the other segments.
+    half_open: bool,
+    /// Whether the first segment excludes its lower bound, which happens when a function
+    /// whose last segment excludes its higher bound is mirrored.
+    open_lowest: bool,
+    /// Function defined on ]-infinity, bound[.
+    below: Option<Unbounded<'a, B, O>>,
+    /// Function defined on [bound, +infinity[.
+    above: Option<Unbounded<'a, B, O>>,
 }
 
-impl<B: PartialOrd, O> PartialFunction<B, O> {
+/// A function defined from a bound up to infinity, on one side of the bound.
+struct Unbounded<'a, B, O> {
+    func: Box<dyn Fn(B) -> O + 'a>,
+    bound: B,
+}
+
+/// The part of a partial function used to evaluate a value.
+#[derive(Clone, Copy)]
+enum Part {
+    /// The function defined below every segment.
+    Below,
+    /// The segment at this index.
+    Segment(usize),
+    /// The function defined above every segment.
+    Above,
+}
+
+impl<'a, B: PartialOrd, O> PartialFunction<'a, B, O> {
     /// Creates a new PartialFunctionBuilder
-    pub fn new() -> PartialFunctionBuilder<B, O> {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> PartialFunctionBuilder<'a, B, O> {
         PartialFunctionBuilder::new()
     }
 
+    /// Returns the number of segments, unbounded segments included.
+    pub fn len(&self) -> usize {
+        self.funcs.len() + self.below.is_some() as usize + self.above.is_some() as usize
+    }
+
+    /// Checks if the function has no segment, in which case it is undefined everywhere.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the bounds of the bounded segment with the lowest bounds.
+    /// Unbounded segments are not included.
+    pub fn first_segment(&self) -> Option<(&B, &B)> {
+        Some((self.lowers.first()?, self.highers.first()?))
+    }
+
+    /// Returns the bounds of the bounded segment with the highest lower bound.
+    /// Unbounded segments are not included.
+    pub fn last_segment(&self) -> Option<(&B, &B)> {
+        Some((self.lowers.last()?, self.highers.last()?))
+    }
+
     /// Evaluates the partial function.
     /// Returns None if no function is defined.
     pub fn eval(&self, x: B) -> Option<O> {
-        let iter = self.funcs.iter().enumerate();
-        for (i, bounded) in iter {
-            let next = self.funcs.get(i + 1);
-            if (x >= bounded.lower && x < bounded.higher)
-                || (next.is_none() && x == bounded.higher)
-                || (next.is_some() && next.unwrap().lower != bounded.higher)
-            {
-                let f = &bounded.func;
-                return Some(f(x));
+        self.eval_indexed(x).map(|(_, y)| y)
+    }
+
+    /// Evaluates the partial function at a value converted to B, like an i32 for f64 bounds.
+    pub fn eval_into(&self, x: impl Into<B>) -> Option<O> {
+        self.eval(x.into())
+    }
+
+    /// Evaluates the partial function and passes a reference to the result to f,
+    /// returning what f returns. The result is dropped afterward.
+    /// Returns None without calling f if no function is defined.
+    pub fn eval_and<R>(&self, x: B, f: impl FnOnce(&O) -> R) -> Option<R> {
+        self.eval(x).map(|y| f(&y))
+    }
+
+    /// Evaluates the partial function, also returning the index of the segment used
+    /// in the segments sorted by lower bound.
+    /// An unbounded segment below all the others comes first and one above comes last,
+    /// as if they were regular segments.
+    /// Returns None if no function is defined.
+    pub fn eval_indexed(&self, x: B) -> Option<(usize, O)> {
+        let part = self.part_of(&x)?;
+        let offset = self.below.is_some() as usize;
+        let index = match part {
+            Part::Below => 0,
+            Part::Segment(i) => i + offset,
+            Part::Above => self.funcs.len() + offset,
+        };
+        let f = self.part_func(part);
+        Some((index, f(x)))
+    }
+
+    /// Returns the bounds of the segment that would be used to evaluate x, without evaluating it.
+    /// Returns None if no function is defined, or if x falls in an unbounded segment
+    /// since those don't have two bounds.
+    pub fn segment_at(&self, x: &B) -> Option<(&B, &B)> {
+        match self.part_of(x)? {
+            Part::Segment(i) => Some((&self.lowers[i], &self.highers[i])),
+            Part::Below | Part::Above => None,
+        }
+    }
+
+    /// Folds over the bounds of the segments in order, calling f with the accumulator and
+    /// the lower and higher bounds of each segment.
+    /// Unbounded segments are skipped since they don't have two bounds.
+    pub fn fold_segments<A>(&self, init: A, mut f: impl FnMut(A, &B, &B) -> A) -> A {
+        self.lowers
+            .iter()
+            .zip(&self.highers)
+            .fold(init, |acc, (lower, higher)| f(acc, lower, higher))
+    }
+
+    /// Returns the function of the segment that would be used to evaluate x.
+    /// Useful to evaluate many values known to be in the same segment without searching it again.
+    pub fn func_at(&self, x: &B) -> Option<&dyn Fn(B) -> O> {
+        let part = self.part_of(x)?;
+        Some(self.part_func(part))
+    }
+
+    /// Replaces the function of the segment bounded by lower and higher.
+    /// Returns false if no segment has these bounds.
+    pub fn replace_func(&mut self, lower: &B, higher: &B, func: Box<dyn Fn(B) -> O + 'a>) -> bool {
+        match (0..self.funcs.len())
+            .find(|&i| &self.lowers[i] == lower && &self.highers[i] == higher)
+        {
+            Some(i) => {
+                self.funcs[i] = func;
+                true
             }
+            None => false,
         }
-        None
+    }
+
+    /// Checks if both functions have the same segment bounds, in the same order.
+    /// The functions stored in the segments are not compared.
+    pub fn structure_eq(&self, other: &Self) -> bool {
+        let same_bound =
+            |a: &Option<Unbounded<'a, B, O>>, b: &Option<Unbounded<'a, B, O>>| match (a, b) {
+                (Some(a), Some(b)) => a.bound == b.bound,
+                (a, b) => a.is_none() && b.is_none(),
+            };
+        same_bound(&self.below, &other.below)
+            && same_bound(&self.above, &other.above)
+            && self.lowers == other.lowers
+            && self.highers == other.highers
+    }
+
+    /// Evaluates the partial function, comparing bounds with cmp instead of partial_cmp.
+    /// cmp must be the comparator used with build_by.
+    /// Returns None if no function is defined.
+    pub fn eval_by(&self, x: B, cmp: impl Fn(&B, &B) -> Ordering) -> Option<O> {
+        let part = self.part_by(&x, &|a: &B, b: &B| Some(cmp(a, b)))?;
+        let f = self.part_func(part);
+        Some(f(x))
+    }
+
+    /// Finds the part of the function to use for x.
+    fn part_of(&self, x: &B) -> Option<Part> {
+        if is_undefined(x) {
+            None
+        } else {
+            self.part_by(x, &B::partial_cmp)
+        }
+    }
+
+    /// Finds the part of the function to use for x, comparing values with cmp.
+    fn part_by<C>(&self, x: &B, cmp: &C) -> Option<Part>
+    where
+        C: Fn(&B, &B) -> Option<Ordering>,
+    {
+        if let Some(above) = &self.above {
+            if matches!(
+                cmp(x, &above.bound),
+                Some(Ordering::Greater) | Some(Ordering::Equal)
+            ) {
+                return Some(Part::Above);
+            }
+        }
+        if let Some(below) = &self.below {
+            if cmp(x, &below.bound) == Some(Ordering::Less) {
+                return Some(Part::Below);
+            }
+        }
+        self.index_by(x, cmp).map(Part::Segment)
+    }
+
+    /// Returns the function of a part found by part_by.
+    fn part_func(&self, part: Part) -> &dyn Fn(B) -> O {
+        match part {
+            Part::Below => &*self.below.as_ref().expect("no function below").func,
+            Part::Segment(i) => &*self.funcs[i],
+            Part::Above => &*self.above.as_ref().expect("no function above").func,
+        }
+    }
+
+    /// Finds the index of the segment to use for x, comparing values with cmp.
+    fn index_by<C>(&self, x: &B, cmp: &C) -> Option<usize>
+    where
+        C: Fn(&B, &B) -> Option<Ordering>,
+    {
+        if self.precedence.is_empty() {
+            // Segments are sorted by lower bound and don't overlap, so only the ones starting
+            // at x and the ones sharing the closest lower bound below x can contain it.
+            let below = |a: &B, b: &B| cmp(a, b) == Some(Ordering::Less);
+            let start = self.lowers.partition_point(|lower| below(lower, x));
+            let first = match start.checked_sub(1) {
+                Some(closest) => {
+                    let closest = &self.lowers[closest];
+                    self.lowers[..start].partition_point(|lower| below(lower, closest))
+                }
+                None => 0,
+            };
+            let end = start + self.lowers[start..].partition_point(|lower| !below(x, lower));
+            (first..end).find(|&i| self.contains(i, x, cmp))
+        } else {
+            (0..self.funcs.len())
+                .filter(|&i| self.contains(i, x, cmp))
+                .max_by_key(|&i| self.precedence[i])
+        }
+    }
+
+    /// Checks if the segment at index i contains x, ignoring the other segments.
+    /// Segments are [lower,higher[, except that the highest higher bound of the segments is
+    /// included unless a point starts there, whether segments can overlap or not.
+    /// The lowest bound is excluded if the function excludes it.
+    fn contains<C>(&self, i: usize, x: &B, cmp: &C) -> bool
+    where
+        C: Fn(&B, &B) -> Option<Ordering>,
+    {
+        let at_end = self
+            .end
+            .as_ref()
+            .is_some_and(|end| cmp(x, end) == Some(Ordering::Equal));
+        in_segment(
+            &self.lowers[i],
+            &self.highers[i],
+            !self.half_open && at_end,
+            x,
+            cmp,
+        ) && (self.is_point(i) || !self.excludes_lowest(x, cmp))
+    }
+
+    /// Checks if x is the lowest bound of the segments while it is excluded.
+    fn excludes_lowest<C>(&self, x: &B, cmp: &C) -> bool
+    where
+        C: Fn(&B, &B) -> Option<Ordering>,
+    {
+        self.open_lowest
+            && self
+                .lowers
+                .first()
+                .is_some_and(|lowest| cmp(x, lowest) == Some(Ordering::Equal))
+    }
+
+    /// Checks if the segment at index i is a point, its lower and higher bounds being equal.
+    fn is_point(&self, i: usize) -> bool {
+        self.lowers[i] == self.highers[i]
+    }
+}
+
+impl<'a, B, O> PartialFunction<'a, B, O> {
+    /// Takes the segments out of the function, leaving it without any.
+    fn take_segments(&mut self) -> Vec<DualBoundedFunction<'a, B, O>> {
+        self.funcs
+            .drain(..)
+            .zip(self.lowers.drain(..).zip(self.highers.drain(..)))
+            .map(|(func, (lower, higher))| DualBoundedFunction {
+                func,
+                lower,
+                higher,
+            })
+            .collect()
+    }
+
+    /// Replaces the segments of the function, which must be sorted.
+    /// The cached data must be rebuilt afterward.
+    fn set_segments(&mut self, segments: Vec<DualBoundedFunction<'a, B, O>>) {
+        self.lowers = Vec::with_capacity(segments.len());
+        self.highers = Vec::with_capacity(segments.len());
+        self.funcs = Vec::with_capacity(segments.len());
+        for b in segments {
+            self.lowers.push(b.lower);
+            self.highers.push(b.higher);
+            self.funcs.push(b.func);
+        }
+    }
+}
+
+impl<'a, B: PartialOrd, O, E> PartialFunction<'a, B, Result<O, E>> {
+    /// Evaluates a function whose segments can fail, returning the error of the segment used.
+    /// Returns Ok(None) if no function is defined.
+    pub fn eval_transpose(&self, x: B) -> Result<Option<O>, E> {
+        self.eval(x).transpose()
+    }
+}
+
+impl<'a, B: PartialOrd + Clone, O> PartialFunction<'a, B, O> {
+    /// Creates the function from sorted segments, computing the cached data.
+    fn from_funcs(funcs: Vec<DualBoundedFunction<'a, B, O>>) -> Self {
+        let mut func = PartialFunction {
+            lowers: vec![],
+            highers: vec![],
+            funcs: vec![],
+            breakpoints: vec![],
+            end: None,
+            precedence: vec![],
+            half_open: false,
+            open_lowest: false,
+            below: None,
+            above: None,
+        };
+        func.set_segments(funcs);
+        func.rebuild_cache();
+        func
+    }
+
+    /// Creates a function from segments in any order, as the builder would.
+    /// Returns an error if a segment has undefined or inverted bounds, or if two segments overlap.
+    pub fn from_segments(
+        segments: Vec<DualBoundedFunction<'a, B, O>>,
+    ) -> Result<Self, InsertError<B>> {
+        for b in &segments {
+            if is_undefined(&b.lower) || is_undefined(&b.higher) {
+                return Err(InsertError::NaNBound(NaNBoundError));
+            }
+            if b.lower > b.higher {
+                return Err(InsertError::InvertedBounds {
+                    lower: b.lower.clone(),
+                    higher: b.higher.clone(),
+                });
+            }
+        }
+        let builder = PartialFunctionBuilder {
+            priorities: vec![0; segments.len()],
+            funcs: segments,
+            overlaps_allowed: false,
+            below: None,
+            above: None,
+        };
+        builder.validate().map_err(InsertError::Overlap)?;
+        Ok(builder.build())
+    }
+
+    /// Creates a function from segments already sorted by lower bound, validating them in a
+    /// single pass instead of checking each pair like from_segments.
+    /// Returns an error if a segment has undefined or inverted bounds, or if a segment overlaps
+    /// the previous one, which includes segments out of order.
+    pub fn from_sorted_segments(
+        segments: Vec<DualBoundedFunction<'a, B, O>>,
+    ) -> Result<Self, InsertError<B>> {
+        for (i, b) in segments.iter().enumerate() {
+            if is_undefined(&b.lower) || is_undefined(&b.higher) {
+                return Err(InsertError::NaNBound(NaNBoundError));
+            }
+            if b.lower > b.higher {
+                return Err(InsertError::InvertedBounds {
+                    lower: b.lower.clone(),
+                    higher: b.higher.clone(),
+                });
+            }
+            if let Some(prev) = i.checked_sub(1).map(|p| &segments[p]) {
+                if b.lower < prev.higher || (prev.is_point() && b.lower == prev.lower) {
+                    let at = if prev.lower > b.lower {
+                        &prev.lower
+                    } else {
+                        &b.lower
+                    };
+                    return Err(InsertError::Overlap(OverlapError {
+                        first: i - 1,
+                        second: i,
+                        at: at.clone(),
+                    }));
+                }
+            }
+        }
+        Ok(PartialFunction::from_funcs(segments))
+    }
+
+    /// Creates a function from segments without validating them, skipping the work done by
+    /// the builder.
+    /// The segments must be sorted by lower bound and must not overlap, as from_sorted_segments
+    /// checks. Otherwise the function is still safe to use but eval can give wrong results.
+    pub fn from_sorted_segments_unchecked(segments: Vec<DualBoundedFunction<'a, B, O>>) -> Self {
+        PartialFunction::from_funcs(segments)
+    }
+
+    /// Turns the function back into a builder holding its segments, so more can be added
+    /// before building it again.
+    ///
+    /// Priorities are kept and overlaps stay allowed if the function had overlapping
+    /// segments, so building again gives the same function. The only exception is a function
+    /// whose last segment excludes its higher bound, like one built with build_strict, which
+    /// must be built with build_strict again, and a mirrored one whose first segment excludes
+    /// its lower bound, which includes it again once built.
+    pub fn into_builder(mut self) -> PartialFunctionBuilder<'a, B, O> {
+        let mut segments = self
+            .take_segments()
+            .into_iter()
+            .zip(
+                self.precedence
+                    .drain(..)
+                    .map(Some)
+                    .chain(std::iter::repeat(None)),
+            )
+            .collect::<Vec<_>>();
+        // Layered segments are reinserted in their insertion order, the last one being used
+        // where they overlap.
+        segments.sort_by_key(|(_, p)| p.map(|(_, index)| index));
+        let (funcs, priorities) = segments
+            .into_iter()
+            .map(|(b, p)| (b, p.map_or(0, |(priority, _)| priority)))
+            .unzip();
+        let mut builder = PartialFunctionBuilder {
+            funcs,
+            priorities,
+            overlaps_allowed: false,
+            below: self.below.take(),
+            above: self.above.take(),
+        };
+        builder.overlaps_allowed = builder.validate().is_err();
+        builder
+    }
+
+    /// Takes the segments of the function, sorted by lower bound.
+    /// Unbounded segments and priorities are not part of the segments and are dropped.
+    /// Iterating over the function gives the same segments.
+    /// They can be turned back into a function without validating them again using
+    /// from_sorted_segments_unchecked.
+    pub fn into_segments(mut self) -> Vec<DualBoundedFunction<'a, B, O>> {
+        self.take_segments()
+    }
+
+    /// Recomputes the cached data derived from the segments.
+    /// Must be called after the segments are modified.
+    pub fn rebuild_cache(&mut self) {
+        let mut breakpoints = self
+            .lowers
+            .iter()
+            .chain(self.highers.iter())
+            .cloned()
+            .chain(self.below.iter().map(|u| u.bound.clone()))
+            .chain(self.above.iter().map(|u| u.bound.clone()))
+            .collect::<Vec<_>>();
+        breakpoints.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        breakpoints.dedup_by(|a, b| a == b);
+        self.breakpoints = breakpoints;
+        self.end = self.end_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    }
+
+    /// Finds the highest higher bound of the segments comparing values with cmp, unless a
+    /// point starts there.
+    fn end_by(&self, cmp: impl Fn(&B, &B) -> Ordering) -> Option<B> {
+        let end = self.highers.iter().max_by(|a, b| cmp(a, b))?;
+        if self
+            .lowers
+            .iter()
+            .any(|lower| cmp(lower, end) == Ordering::Equal)
+        {
+            None
+        } else {
+            Some(end.clone())
+        }
+    }
+
+    /// Returns the sorted bounds of all segments, without duplicates.
+    pub fn breakpoints(&self) -> &[B] {
+        &self.breakpoints
+    }
+
+    /// Returns the lowest and highest bounds of the function.
+    /// The bounds of unbounded segments are included, although the function is defined past them.
+    /// Returns None if the function has no segment.
+    pub fn domain(&self) -> Option<(B, B)> {
+        let lower = self.breakpoints.first()?;
+        let higher = self.breakpoints.last()?;
+        Some((lower.clone(), higher.clone()))
+    }
+
+    /// Evaluates the partial function, evaluating values below the domain at its lowest bound
+    /// and values above the domain at its highest bound.
+    /// A bound excluded by the function, like the highest bound of one built with build_strict,
+    /// is evaluated by the segment ending or starting there.
+    /// Gaps inside the domain still return None, as does an empty function.
+    pub fn eval_clamped(&self, x: B) -> Option<O> {
+        let lower = self.breakpoints.first()?;
+        let higher = self.breakpoints.last()?;
+        if &x < lower && self.below.is_none() {
+            self.eval(lower.clone())
+                .or_else(|| self.eval_edge(&self.lowers, lower))
+        } else if &x > higher && self.above.is_none() {
+            self.eval(higher.clone())
+                .or_else(|| self.eval_edge(&self.highers, higher))
+        } else {
+            self.eval(x)
+        }
+    }
+
+    /// Evaluates x with the segment having it as one of its bounds, even if that segment excludes it.
+    /// The segment with the greatest precedence is used if several do.
+    fn eval_edge(&self, bounds: &[B], x: &B) -> Option<O> {
+        let i = (0..self.funcs.len())
+            .filter(|&i| &bounds[i] == x)
+            .max_by_key(|&i| self.precedence.get(i).copied())?;
+        Some(self.funcs[i](x.clone()))
+    }
+
+    /// Evaluates the partial function, telling why it isn't defined at x if it isn't.
+    /// The value is defined exactly where eval returns Some.
+    pub fn try_eval(&self, x: B) -> Result<O, EvalError<B>> {
+        if is_undefined(&x) {
+            return Err(EvalError::NaN);
+        }
+        let (min, max) = self.domain().ok_or(EvalError::Empty)?;
+        if x < min {
+            return Err(EvalError::BelowDomain { min });
+        }
+        if x > max {
+            return Err(EvalError::AboveDomain { max });
+        }
+        if let Some(y) = self.eval(x.clone()) {
+            return Ok(y);
+        }
+        // The ends of the domain are only undefined when the function excludes them.
+        if x == min && self.below.is_none() {
+            return Err(EvalError::BelowDomain { min });
+        }
+        if x == max && self.above.is_none() {
+            return Err(EvalError::AboveDomain { max });
+        }
+        // Within the domain, undefined values are between the closest higher bound at or
+        // before them and the closest lower bound after them.
+        let below = self.below.iter().map(|u| &u.bound);
+        let above = self.above.iter().map(|u| &u.bound);
+        let gap_start = self
+            .highers
+            .iter()
+            .chain(below)
+            .filter(|&h| h <= &x)
+            .fold(&min, |a, h| if h > a { h } else { a });
+        let gap_end = self
+            .lowers
+            .iter()
+            .chain(above)
+            .filter(|&l| l > &x)
+            .fold(&max, |a, l| if l < a { l } else { a });
+        Err(EvalError::InGap {
+            gap_start: gap_start.clone(),
+            gap_end: gap_end.clone(),
+        })
+    }
+
+    /// Returns the intervals between consecutive segments where no function is defined.
+    /// Overlapping segments are merged first, so a segment ending inside another one
+    /// leaves no gap.
+    pub fn gaps(&self) -> Vec<(B, B)> {
+        // Unbounded segments only matter through their bound.
+        let unbounded = |u: &Unbounded<'a, B, O>| (u.bound.clone(), u.bound.clone());
+        let spans = self
+            .below
+            .iter()
+            .map(unbounded)
+            .chain(
+                self.lowers
+                    .iter()
+                    .cloned()
+                    .zip(self.highers.iter().cloned()),
+            )
+            .chain(self.above.iter().map(unbounded));
+        let mut gaps = vec![];
+        // Highest bound covered by the spans seen so far.
+        let mut reach: Option<B> = None;
+        for (lower, higher) in spans {
+            if let Some(reach) = &reach {
+                if reach < &lower {
+                    gaps.push((reach.clone(), lower));
+                }
+            }
+            if reach.as_ref().is_none_or(|reach| reach < &higher) {
+                reach = Some(higher);
+            }
+        }
+        gaps
+    }
+
+    /// Checks if the function is defined everywhere between its lowest and highest bounds,
+    /// meaning each segment touches or overlaps the next one, as found by gaps.
+    /// An empty function has no gap and is continuous.
+    pub fn is_continuous(&self) -> bool {
+        self.gaps().is_empty()
+    }
+
+    /// Evaluates the partial function for each value of xs, which must be sorted in increasing order.
+    /// Walks the segments and the values together instead of searching the segments for each value.
+    pub fn eval_sorted(&self, xs: &[B]) -> Vec<Option<O>> {
+        debug_assert!(xs.windows(2).all(|w| w[0] <= w[1]));
+        self.eval_batch_indexed(xs)
+            .into_iter()
+            .map(|result| result.map(|(_, y)| y))
+            .collect()
+    }
+
+    /// Evaluates the partial function for each value of xs, also returning the index of the
+    /// segment used like eval_indexed.
+    /// Sorted values are evaluated in a single walk through the segments, like eval_sorted.
+    /// Unsorted values, including values containing a NaN, silently fall back to searching
+    /// each value like eval_indexed, which gives the same results but is slower for big batches.
+    pub fn eval_batch_indexed(&self, xs: &[B]) -> Vec<Option<(usize, O)>> {
+        let sorted = xs.windows(2).all(|w| w[0] <= w[1]);
+        if !sorted || !self.precedence.is_empty() || self.below.is_some() || self.above.is_some() {
+            return xs.iter().map(|x| self.eval_indexed(x.clone())).collect();
+        }
+        let mut i = 0;
+        xs.iter()
+            .map(|x| {
+                while i + 1 < self.funcs.len()
+                    && (x > &self.highers[i] || (x == &self.highers[i] && !self.is_point(i)))
+                {
+                    i += 1;
+                }
+                if i < self.funcs.len() && !is_undefined(x) && self.contains(i, x, &B::partial_cmp)
+                {
+                    let f = &self.funcs[i];
+                    Some((i, f(x.clone())))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+impl<'a, B: PartialOrd + Clone + Sub<Output = B>, O> PartialFunction<'a, B, O> {
+    /// Evaluates the partial function and tells if the result is stable.
+    /// The result is considered unstable (false) when x is within margin of any segment bound,
+    /// where a tiny change of x could switch to another segment.
+    pub fn eval_stable(&self, x: B, margin: B) -> Option<(O, bool)> {
+        let near = |bound: &B| {
+            let distance = if &x >= bound {
+                x.clone() - bound.clone()
+            } else {
+                bound.clone() - x.clone()
+            };
+            distance <= margin
+        };
+        let stable = !self.breakpoints.iter().any(near);
+        self.eval(x).map(|o| (o, stable))
+    }
+}
+
+impl<'a, O> PartialFunction<'a, f64, O> {
+    /// Lazily evaluates the function at count values, starting at start and increasing by step.
+    /// A negative step walks the function backward.
+    pub fn walk(
+        &self,
+        start: f64,
+        step: f64,
+        count: usize,
+    ) -> impl Iterator<Item = (f64, Option<O>)> + '_ {
+        (0..count).map(move |i| {
+            let x = start + i as f64 * step;
+            (x, self.eval(x))
+        })
+    }
+
+    /// Evaluates each segment at its midpoint, giving one (midpoint, value) per segment in order.
+    /// Unbounded segments don't have a midpoint and are skipped.
+    pub fn segment_samples(&self) -> impl Iterator<Item = (f64, O)> + '_ {
+        self.lowers
+            .iter()
+            .zip(&self.highers)
+            .filter_map(move |(lower, higher)| {
+                let mid = (lower + higher) / 2.0;
+                self.eval(mid).map(|y| (mid, y))
+            })
+    }
+
+    /// Evaluates the partial function, or the closest segment at its nearest bound when x
+    /// falls in a gap of the domain.
+    /// When x is exactly between two segments, the lower one is used.
+    /// Values outside of the domain still return None, see eval_clamped for those.
+    pub fn eval_nearest(&self, x: f64) -> Option<O> {
+        if let Some(y) = self.eval(x) {
+            return Some(y);
+        }
+        let (lower, higher) = self.domain()?;
+        if !(lower..=higher).contains(&x) {
+            return None;
+        }
+        let (edge, f) = self
+            .below
+            .iter()
+            .map(|u| (u.bound, &*u.func))
+            .chain((0..self.funcs.len()).flat_map(|i| {
+                [
+                    (self.lowers[i], &*self.funcs[i]),
+                    (self.highers[i], &*self.funcs[i]),
+                ]
+            }))
+            .chain(self.above.iter().map(|u| (u.bound, &*u.func)))
+            .min_by(|a, b| {
+                (a.0 - x)
+                    .abs()
+                    .partial_cmp(&(b.0 - x).abs())
+                    .unwrap_or(Ordering::Equal)
+            })?;
+        Some(f(edge))
+    }
+
+    /// Evaluates the partial function at x, also evaluating the following segment at its lower
+    /// bound, which gives the control points to blend across the boundary.
+    ///
+    /// The following segment is the next one by lower bound, or the segment unbounded above.
+    /// Returns None if no function is defined at x, and no second value if x is in the last
+    /// segment.
+    pub fn eval_with_next(&self, x: f64) -> Option<(O, Option<O>)> {
+        let part = self.part_of(&x)?;
+        let y = self.part_func(part)(x);
+        let next_segment = match part {
+            Part::Below => 0,
+            Part::Segment(i) => i + 1,
+            Part::Above => return Some((y, None)),
+        };
+        let next = match self.funcs.get(next_segment) {
+            Some(f) => Some(f(self.lowers[next_segment])),
+            None => self.above.as_ref().map(|u| (u.func)(u.bound)),
+        };
+        Some((y, next))
+    }
+
+    /// Returns the distance from x to the nearest bound of the segment it falls in.
+    /// Unbounded segments only have one bound to measure from.
+    /// Returns None if no function is defined at x.
+    pub fn edge_distance(&self, x: f64) -> Option<f64> {
+        let distance = match self.part_of(&x)? {
+            Part::Below => self.below.as_ref()?.bound - x,
+            Part::Segment(i) => (x - self.lowers[i]).min(self.highers[i] - x),
+            Part::Above => x - self.above.as_ref()?.bound,
+        };
+        Some(distance)
+    }
+}
+
+impl<'a, O> PartialFunction<'a, i32, O> {
+    /// Counts the integers in [a, b] where the function is defined, without evaluating it.
+    ///
+    /// The integers inside each segment are counted at once and only the higher bounds, where
+    /// whether the function is defined depends on the other segments, are checked one by one.
+    pub fn defined_count(&self, a: i32, b: i32) -> usize {
+        let (a, b) = (i64::from(a), i64::from(b));
+        // Integer ranges [start, end] surely defined, higher bounds excluded.
+        let mut ranges = self
+            .lowers
+            .iter()
+            .zip(&self.highers)
+            .map(|(&lower, &higher)| (i64::from(lower), i64::from(higher) - 1))
+            .chain(
+                self.below
+                    .iter()
+                    .map(|u| (i64::MIN, i64::from(u.bound) - 1)),
+            )
+            .chain(self.above.iter().map(|u| (i64::from(u.bound), i64::MAX)))
+            .map(|(start, end)| (start.max(a), end.min(b)))
+            .filter(|(start, end)| start <= end)
+            .collect::<Vec<_>>();
+        ranges.sort_unstable();
+        let mut merged: Vec<(i64, i64)> = vec![];
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        let inside = merged
+            .iter()
+            .map(|(start, end)| (end - start + 1) as usize)
+            .sum::<usize>();
+        let covered = |x: i64| merged.iter().any(|&(start, end)| start <= x && x <= end);
+        let mut edges = self
+            .highers
+            .iter()
+            .copied()
+            .filter(|&h| a <= i64::from(h) && i64::from(h) <= b && !covered(i64::from(h)))
+            .collect::<Vec<_>>();
+        edges.sort_unstable();
+        edges.dedup();
+        let at_edges = edges
+            .into_iter()
+            .filter(|h| self.part_of(h).is_some())
+            .count();
+        inside + at_edges
     }
 }
 
 /// A builder to create an immutable PartialFunction.
 #[derive(new)]
-pub struct PartialFunctionBuilder<B, O> {
+pub struct PartialFunctionBuilder<'a, B, O> {
+    #[new(default)]
+    funcs: Vec<DualBoundedFunction<'a, B, O>>,
+    /// Priority of each segment, aligned with funcs.
+    #[new(default)]
+    priorities: Vec<i32>,
+    /// Whether segments of the same priority can overlap.
+    #[new(default)]
+    overlaps_allowed: bool,
+    #[new(default)]
+    below: Option<Unbounded<'a, B, O>>,
     #[new(default)]
-    funcs: Vec<DualBoundedFunction<B, O>>,
+    above: Option<Unbounded<'a, B, O>>,
 }
 
-impl<B: PartialOrd, O> PartialFunctionBuilder<B, O> {
+impl<'a, B: PartialOrd + Debug, O> PartialFunctionBuilder<'a, B, O> {
     /// Adds a bounded function bounded between [lower,higher[ of function func.
-    pub fn with(mut self, lower: B, higher: B, func: Box<dyn Fn(B) -> O>) -> Self {
-        debug_assert!(self.can_insert(&lower, &higher));
+    ///
+    /// Equal bounds add a point segment, defined only at that value. Inverted bounds, where
+    /// lower is above higher, are rejected by can_insert and panic in debug builds; use
+    /// try_with to get an InsertError::InvertedBounds instead.
+    pub fn with(self, lower: B, higher: B, func: Box<dyn Fn(B) -> O + 'a>) -> Self {
+        self.with_priority(lower, higher, 0, func)
+    }
+
+    /// Adds a bounded function bounded between [lower,higher[ of function func, which can
+    /// overlap segments of other priorities.
+    ///
+    /// Where segments overlap, eval uses the one with the highest priority.
+    /// Segments of the same priority can't overlap, except on the highest bound of the
+    /// whole function where the most recently inserted one is used.
+    /// Segments added using with have a priority of 0.
+    pub fn with_priority(
+        mut self,
+        lower: B,
+        higher: B,
+        priority: i32,
+        func: Box<dyn Fn(B) -> O + 'a>,
+    ) -> Self {
+        self.insert(lower, higher, priority, func);
+        self
+    }
+
+    /// Adds a segment, with the same checks as with_priority.
+    fn insert(&mut self, lower: B, higher: B, priority: i32, func: Box<dyn Fn(B) -> O + 'a>) {
+        debug_assert!(
+            !is_undefined(&lower) && !is_undefined(&higher),
+            "bounds of a segment can't be NaN"
+        );
+        debug_assert!(
+            lower <= higher,
+            "segment [{:?}, {:?}) has inverted bounds, lower can't be above higher",
+            lower,
+            higher
+        );
+        if cfg!(debug_assertions)
+            && !self.can_insert_priority(&lower, &higher, priority)
+            && !(self.overlaps_allowed && lower <= higher)
+        {
+            match self.overlapping(&lower, &higher, priority) {
+                Some(i) if !self.overlaps_allowed => panic!(
+                    "segment [{:?}, {:?}) overlaps segment {} [{:?}, {:?}) of priority {}",
+                    lower, higher, i, self.funcs[i].lower, self.funcs[i].higher, priority
+                ),
+                _ => panic!(
+                    "segment [{:?}, {:?}) overlaps an unbounded segment",
+                    lower, higher
+                ),
+            }
+        }
         let f = DualBoundedFunction {
-            func: func,
-            lower: lower,
-            higher: higher,
+            func,
+            lower,
+            higher,
         };
         self.funcs.push(f);
+        self.priorities.push(priority);
+    }
+
+    /// Adds each (lower, higher, func) of iter, like with.
+    pub fn extend<I>(mut self, iter: I) -> Self
+    where
+        I: IntoIterator<Item = (B, B, Box<dyn Fn(B) -> O + 'a>)>,
+    {
+        Extend::extend(&mut self, iter);
+        self
+    }
+
+    /// Adds a segment bounded between [lower,higher[ always returning value.
+    pub fn with_const(self, lower: B, higher: B, value: O) -> Self
+    where
+        O: Clone + 'a,
+    {
+        self.with(lower, higher, Box::new(move |_| value.clone()))
+    }
+
+    /// Adds a function defined only at x.
+    pub fn with_point(self, x: B, func: Box<dyn Fn(B) -> O + 'a>) -> Self
+    where
+        B: Clone,
+    {
+        self.with(x.clone(), x, func)
+    }
+}
+
+impl<'a, B: PartialOrd, O> PartialFunctionBuilder<'a, B, O> {
+    /// Allows segments to overlap, the most recently inserted segment containing x being used
+    /// by eval.
+    /// Segments of a higher priority still take precedence over the others.
+    pub fn allow_overlaps(mut self) -> Self {
+        self.overlaps_allowed = true;
+        self
+    }
+
+    /// Adds a function defined on ]-infinity, higher[.
+    pub fn with_unbounded_lower(mut self, higher: B, func: Box<dyn Fn(B) -> O + 'a>) -> Self {
+        debug_assert!(self.can_insert_unbounded_lower(&higher));
+        self.below = Some(Unbounded {
+            func,
+            bound: higher,
+        });
+        self
+    }
+
+    /// Adds a function defined on [lower, +infinity[.
+    pub fn with_unbounded_upper(mut self, lower: B, func: Box<dyn Fn(B) -> O + 'a>) -> Self {
+        debug_assert!(self.can_insert_unbounded_upper(&lower));
+        self.above = Some(Unbounded { func, bound: lower });
         self
     }
 
     /// Check if you can safely insert into the function list for the specified bounds.
+    /// Bounds that can't be compared or where lower is above higher are rejected.
     pub fn can_insert(&self, lower: &B, higher: &B) -> bool {
-        !self.funcs.iter().any(|b| {
-            (lower >= &b.lower && lower < &b.higher)
-                || (higher > &b.lower && higher <= &b.higher)
-                || (lower <= &b.lower && higher >= &b.higher)
+        self.can_insert_priority(lower, higher, 0)
+    }
+
+    /// Check if you can safely insert into the function list for the specified bounds
+    /// and priority. Only segments of the same priority are checked for overlaps.
+    pub fn can_insert_priority(&self, lower: &B, higher: &B, priority: i32) -> bool {
+        !is_undefined(lower)
+            && !is_undefined(higher)
+            && lower <= higher
+            && self.overlapping(lower, higher, priority).is_none()
+            && !self.overlaps_unbounded(lower, higher)
+    }
+
+    /// Finds the first segment of the given priority overlapping the bounds.
+    fn overlapping(&self, lower: &B, higher: &B, priority: i32) -> Option<usize> {
+        self.funcs
+            .iter()
+            .zip(&self.priorities)
+            .position(|(b, &p)| p == priority && overlaps(lower, higher, &b.lower, &b.higher))
+    }
+
+    /// Check if you can safely insert a function defined on ]-infinity, higher[.
+    /// There can only be one such function.
+    pub fn can_insert_unbounded_lower(&self, higher: &B) -> bool {
+        self.below.is_none()
+            && !is_undefined(higher)
+            && self.funcs.iter().all(|b| &b.lower >= higher)
+            && self.above.as_ref().is_none_or(|u| &u.bound >= higher)
+    }
+
+    /// Check if you can safely insert a function defined on [lower, +infinity[.
+    /// There can only be one such function.
+    pub fn can_insert_unbounded_upper(&self, lower: &B) -> bool {
+        self.above.is_none()
+            && !is_undefined(lower)
+            && self
+                .funcs
+                .iter()
+                .all(|b| &b.higher <= lower && (!b.is_point() || &b.lower < lower))
+            && self.below.as_ref().is_none_or(|u| &u.bound <= lower)
+    }
+
+    /// Checks if the segment bounded by lower and higher overlaps an unbounded function.
+    fn overlaps_unbounded(&self, lower: &B, higher: &B) -> bool {
+        let below = self.below.as_ref().is_some_and(|u| lower < &u.bound);
+        let above = self
+            .above
+            .as_ref()
+            .is_some_and(|u| higher > &u.bound || (lower == higher && lower >= &u.bound));
+        below || above
+    }
+}
+
+/// Checks if x is in the segment [lower,higher[, following the rules shared by all function
+/// types: a point contains its value and the higher bound is only included if higher_included
+/// is true, which is the case for the last segment of a function.
+pub(crate) fn in_segment<B, C>(lower: &B, higher: &B, higher_included: bool, x: &B, cmp: &C) -> bool
+where
+    C: Fn(&B, &B) -> Option<Ordering>,
+{
+    let is = |a: &B, b: &B, o: Ordering| cmp(a, b) == Some(o);
+    let at_least = |a: &B, b: &B| is(a, b, Ordering::Greater) || is(a, b, Ordering::Equal);
+    (at_least(x, lower) && is(x, higher, Ordering::Less))
+        || (is(lower, higher, Ordering::Equal) && is(x, lower, Ordering::Equal))
+        || (higher_included && is(x, higher, Ordering::Equal))
+}
+
+/// Checks if the segment bounded by lower and higher overlaps the segment bounded by
+/// other_lower and other_higher.
+pub(crate) fn overlaps<B: PartialOrd>(
+    lower: &B,
+    higher: &B,
+    other_lower: &B,
+    other_higher: &B,
+) -> bool {
+    let other_is_point = other_lower == other_higher;
+    if lower == higher {
+        // Points only conflict with the segments containing them.
+        lower >= other_lower && (lower < other_higher || (other_is_point && lower == other_lower))
+    } else if other_is_point {
+        other_lower >= lower && other_lower < higher
+    } else {
+        (lower >= other_lower && lower < other_higher)
+            || (higher > other_lower && higher <= other_higher)
+            || (lower <= other_lower && higher >= other_higher)
+    }
+}
+
+impl<'a, B: PartialOrd + Clone + 'a, O: 'a> PartialFunctionBuilder<'a, B, O> {
+    /// Splits the first inserted segment strictly containing x into [lower,x[ and [x,higher[,
+    /// both using its function, which becomes shared between them.
+    /// The new segments keep the priority and the insertion order of the split one.
+    /// Returns false if no segment contains x other than at its bounds.
+    pub fn split_at(&mut self, x: B) -> bool {
+        let i = match self.funcs.iter().position(|b| b.lower < x && x < b.higher) {
+            Some(i) => i,
+            None => return false,
+        };
+        let b = self.funcs.remove(i);
+        let f = Rc::new(b.func);
+        let left = DualBoundedFunction {
+            func: shared(&f),
+            lower: b.lower,
+            higher: x.clone(),
+        };
+        let right = DualBoundedFunction {
+            func: shared(&f),
+            lower: x,
+            higher: b.higher,
+        };
+        self.funcs.insert(i, right);
+        self.funcs.insert(i, left);
+        self.priorities.insert(i, self.priorities[i]);
+        true
+    }
+}
+
+impl<'a, B: PartialOrd + Clone, O> PartialFunctionBuilder<'a, B, O> {
+    /// Adds segments always returning value wherever no segment is defined in [from, to].
+    ///
+    /// Since a segment can't start right after a point segment, a gap following a point
+    /// segment is left as is.
+    pub fn fill_gaps(self, from: B, to: B, value: O) -> Self
+    where
+        B: Debug,
+        O: Clone + 'a,
+    {
+        let mut covered = self
+            .funcs
+            .iter()
+            .map(|b| (b.lower.clone(), b.higher.clone()))
+            .collect::<Vec<_>>();
+        covered.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+        let mut cursor = match &self.below {
+            Some(u) if u.bound > from => u.bound.clone(),
+            _ => from,
+        };
+        let end = match &self.above {
+            Some(u) if u.bound < to => u.bound.clone(),
+            _ => to,
+        };
+        let mut gaps = vec![];
+        let mut after_point = false;
+        for (lower, higher) in covered {
+            if lower > cursor && !after_point {
+                let gap_end = if lower < end {
+                    lower.clone()
+                } else {
+                    end.clone()
+                };
+                if cursor < gap_end {
+                    gaps.push((cursor.clone(), gap_end));
+                }
+            }
+            if lower == higher && lower >= cursor {
+                after_point = true;
+                cursor = higher;
+            } else if higher > cursor {
+                after_point = false;
+                cursor = higher;
+            }
+        }
+        if cursor < end && !after_point {
+            gaps.push((cursor, end));
+        }
+        gaps.into_iter().fold(self, |builder, (lower, higher)| {
+            builder.with_const(lower, higher, value.clone())
         })
     }
 
-    /// Builds the PartialFunction from the functions added using with.
-    pub fn build(mut self) -> PartialFunction<B, O> {
-        self.funcs.sort_by(|a, b| {
+    /// Adds a bounded function bounded between [lower,higher[ of function func.
+    /// Returns an error instead of inserting if can_insert would return false.
+    pub fn try_with(
+        self,
+        lower: B,
+        higher: B,
+        func: Box<dyn Fn(B) -> O + 'a>,
+    ) -> Result<Self, InsertError<B>>
+    where
+        B: Debug,
+    {
+        if is_undefined(&lower) || is_undefined(&higher) {
+            return Err(InsertError::NaNBound(NaNBoundError));
+        }
+        if lower > higher {
+            return Err(InsertError::InvertedBounds { lower, higher });
+        }
+        if self.overlaps_unbounded(&lower, &higher) {
+            return Err(InsertError::OverlapUnbounded { lower, higher });
+        }
+        let conflict = if self.overlaps_allowed {
+            None
+        } else {
+            self.overlapping(&lower, &higher, 0)
+        };
+        if let Some(first) = conflict {
+            let existing = &self.funcs[first];
+            let at = if existing.lower > lower {
+                existing.lower.clone()
+            } else {
+                lower
+            };
+            return Err(InsertError::Overlap(OverlapError {
+                first,
+                second: self.funcs.len(),
+                at,
+            }));
+        }
+        Ok(self.with(lower, higher, func))
+    }
+
+    /// Checks that no two segments of the same priority overlap, reporting the first conflict
+    /// in insertion order.
+    /// If this succeeds, build will succeed too.
+    pub fn validate(&self) -> Result<(), OverlapError<B>> {
+        for (second, b) in self.funcs.iter().enumerate() {
+            let priority = self.priorities[second];
+            let conflict = self.funcs[..second]
+                .iter()
+                .zip(&self.priorities)
+                .position(|(a, &p)| {
+                    p == priority && overlaps(&b.lower, &b.higher, &a.lower, &a.higher)
+                });
+            if let Some(first) = conflict {
+                let a = &self.funcs[first];
+                let at = if a.lower > b.lower {
+                    &a.lower
+                } else {
+                    &b.lower
+                };
+                return Err(OverlapError {
+                    first,
+                    second,
+                    at: at.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds all the segments of iter, validating them together with a single sort instead of
+    /// checking each of them against all the others like with.
+    ///
+    /// The segments are added sorted by lower bound, so building only checks their order when
+    /// the builder had no segment before or only lower ones.
+    /// Returns every problem found, in which case nothing is added. Segments are reported by
+    /// their insertion index as if they were added in the order of iter, and overlaps are only
+    /// checked once all the bounds are valid.
+    pub fn with_many<I>(mut self, iter: I) -> Result<Self, BuildError<B>>
+    where
+        I: IntoIterator<Item = (B, B, Box<dyn Fn(B) -> O + 'a>)>,
+    {
+        let start = self.funcs.len();
+        let mut added = iter
+            .into_iter()
+            .enumerate()
+            .map(|(i, (lower, higher, func))| {
+                (start + i, DualBoundedFunction::new(func, lower, higher))
+            })
+            .collect::<Vec<_>>();
+        let mut errors = vec![];
+        for (_, b) in &added {
+            if is_undefined(&b.lower) || is_undefined(&b.higher) {
+                errors.push(InsertError::NaNBound(NaNBoundError));
+            } else if b.lower > b.higher {
+                errors.push(InsertError::InvertedBounds {
+                    lower: b.lower.clone(),
+                    higher: b.higher.clone(),
+                });
+            } else if self.overlaps_unbounded(&b.lower, &b.higher) {
+                errors.push(InsertError::OverlapUnbounded {
+                    lower: b.lower.clone(),
+                    higher: b.higher.clone(),
+                });
+            }
+        }
+        let by_bounds = |a: &DualBoundedFunction<'a, B, O>, b: &DualBoundedFunction<'a, B, O>| {
             a.lower
                 .partial_cmp(&b.lower)
-                .unwrap_or(a.higher.partial_cmp(&b.higher).unwrap_or(Ordering::Equal))
-        });
-        PartialFunction { funcs: self.funcs }
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| a.higher.partial_cmp(&b.higher).unwrap_or(Ordering::Equal))
+        };
+        added.sort_by(|(_, a), (_, b)| by_bounds(a, b));
+        if errors.is_empty() && !self.overlaps_allowed {
+            // Segments of priority 0 sorted by bounds, old and new.
+            let mut all = self
+                .funcs
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| self.priorities[i] == 0)
+                .chain(added.iter().map(|(i, b)| (*i, b)))
+                .collect::<Vec<_>>();
+            all.sort_by(|(_, a), (_, b)| by_bounds(a, b));
+            // Segment reaching the highest so far, which any overlapping segment overlaps too.
+            let mut reach: Option<(usize, &DualBoundedFunction<'a, B, O>)> = None;
+            for (i, b) in all {
+                if let Some((first, r)) = reach {
+                    if (first >= start || i >= start)
+                        && overlaps(&b.lower, &b.higher, &r.lower, &r.higher)
+                    {
+                        let at = if r.lower > b.lower {
+                            &r.lower
+                        } else {
+                            &b.lower
+                        };
+                        errors.push(InsertError::Overlap(OverlapError {
+                            first: first.min(i),
+                            second: first.max(i),
+                            at: at.clone(),
+                        }));
+                    }
+                }
+                if reach.is_none_or(|(_, r)| b.higher >= r.higher) {
+                    reach = Some((i, b));
+                }
+            }
+        }
+        if !errors.is_empty() {
+            return Err(BuildError { errors });
+        }
+        for (_, b) in added {
+            self.funcs.push(b);
+            self.priorities.push(0);
+        }
+        Ok(self)
+    }
+
+    /// Builds the PartialFunction from the functions added using with.
+    ///
+    /// Segments are sorted by lower bound with a stable sort, so segments sharing a lower bound,
+    /// which is only possible with priorities or allow_overlaps, keep their insertion order.
+    pub fn build(mut self) -> PartialFunction<'a, B, O> {
+        let order = self.sort_by(|a, b| a.lower.partial_cmp(&b.lower).unwrap_or(Ordering::Equal));
+        let layered = self.overlaps_allowed;
+        self.into_function(order, layered)
+    }
+
+    /// Builds the PartialFunction with every segment being [lower,higher[, the last one
+    /// included, so the function is undefined at its highest bound.
+    ///
+    /// This keeps the same half-open rule everywhere, which is easier to reason about when
+    /// stitching functions together. Point segments are still defined at their value.
+    pub fn build_strict(self) -> PartialFunction<'a, B, O> {
+        let mut func = self.build();
+        func.half_open = true;
+        func
+    }
+
+    /// Sorts the segments with cmp, returning the insertion index of each sorted segment.
+    fn sort_by<C>(&mut self, cmp: C) -> Vec<usize>
+    where
+        C: Fn(&DualBoundedFunction<'a, B, O>, &DualBoundedFunction<'a, B, O>) -> Ordering,
+    {
+        let mut indexed = self.funcs.drain(..).enumerate().collect::<Vec<_>>();
+        indexed.sort_by(|(_, a), (_, b)| cmp(a, b));
+        let (order, funcs) = indexed.into_iter().unzip();
+        self.funcs = funcs;
+        order
+    }
+
+    /// Creates the function from the sorted segments and their insertion index.
+    /// Segments are layered if asked to or if any of them has a priority.
+    fn into_function(self, order: Vec<usize>, layered: bool) -> PartialFunction<'a, B, O> {
+        let precedence = if layered || self.priorities.iter().any(|&p| p != 0) {
+            order.into_iter().map(|i| (self.priorities[i], i)).collect()
+        } else {
+            vec![]
+        };
+        let mut func = PartialFunction::from_funcs(self.funcs);
+        func.precedence = precedence;
+        if self.below.is_some() || self.above.is_some() {
+            func.below = self.below;
+            func.above = self.above;
+            func.rebuild_cache();
+        }
+        func
+    }
+
+    /// Builds the PartialFunction, sorting the segments with cmp instead of partial_cmp.
+    /// Useful for bounds where partial_cmp doesn't give a total order.
+    /// The resulting function must be evaluated using eval_by with the same comparator.
+    pub fn build_by(mut self, cmp: impl Fn(&B, &B) -> Ordering) -> PartialFunction<'a, B, O> {
+        let order =
+            self.sort_by(|a, b| cmp(&a.lower, &b.lower).then_with(|| cmp(&a.higher, &b.higher)));
+        let layered = self.overlaps_allowed;
+        let mut func = self.into_function(order, layered);
+        func.breakpoints.sort_by(&cmp);
+        func.breakpoints
+            .dedup_by(|a, b| cmp(a, b) == Ordering::Equal);
+        func.end = func.end_by(&cmp);
+        func
+    }
+
+    /// Builds the PartialFunction, giving precedence to the most recently inserted segment
+    /// when several contain the evaluated value.
+    ///
+    /// All segments are treated as [lower,higher[, except that the highest bound of the
+    /// whole function is inclusive.
+    /// Segments are still stored sorted by lower bound for the other operations.
+    pub fn build_preserve_order(mut self) -> PartialFunction<'a, B, O> {
+        let order = self.sort_by(|a, b| a.lower.partial_cmp(&b.lower).unwrap_or(Ordering::Equal));
+        self.into_function(order, true)
+    }
+}
+
+impl<'a, B, O> IntoIterator for PartialFunction<'a, B, O> {
+    type Item = DualBoundedFunction<'a, B, O>;
+    type IntoIter = std::vec::IntoIter<DualBoundedFunction<'a, B, O>>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.take_segments().into_iter()
+    }
+}
+
+/// An empty function, undefined everywhere.
+impl<'a, B: PartialOrd + Clone, O> Default for PartialFunction<'a, B, O> {
+    fn default() -> Self {
+        PartialFunction::from_funcs(vec![])
+    }
+}
+
+impl<'a, B, O> Default for PartialFunctionBuilder<'a, B, O> {
+    fn default() -> Self {
+        PartialFunctionBuilder::new()
+    }
+}
+
+/// Adds segments as (lower, higher, func), like with.
+impl<'a, B: PartialOrd + Debug, O> Extend<(B, B, Box<dyn Fn(B) -> O + 'a>)>
+    for PartialFunctionBuilder<'a, B, O>
+{
+    fn extend<I: IntoIterator<Item = (B, B, Box<dyn Fn(B) -> O + 'a>)>>(&mut self, iter: I) {
+        for (lower, higher, func) in iter {
+            self.insert(lower, higher, 0, func);
+        }
+    }
+}
+
+impl<'a, B: PartialOrd + Debug, O> FromIterator<(B, B, Box<dyn Fn(B) -> O + 'a>)>
+    for PartialFunctionBuilder<'a, B, O>
+{
+    fn from_iter<I: IntoIterator<Item = (B, B, Box<dyn Fn(B) -> O + 'a>)>>(iter: I) -> Self {
+        PartialFunctionBuilder::new().extend(iter)
     }
 }
 
 /// A lower bounded function is a function that is valid from [x..infinite[, or until it hits another function's start.
 #[derive(new)]
-struct LowerBoundedFunction<B, O> {
+pub struct LowerBoundedFunction<'a, B, O> {
     /// The stored function f(x) = ???
-    pub func: Box<dyn Fn(B) -> O>,
+    pub func: Box<dyn Fn(B) -> O + 'a>,
     /// The lower bound of the function.
     pub lower: B,
 }
@@ -108,86 +1528,729 @@ struct LowerBoundedFunction<B, O> {
 /// f(0.5) = 5
 /// f(1) = 10
 /// f(70) = 10
-pub struct LowerPartialFunction<B, O>
+///
+/// Each segment can also carry a label of type L, like a name to display for the segment.
+///
+/// The functions can borrow data living for 'a. Before 0.6 the type had no lifetime parameter:
+/// write LowerPartialFunction<'static, B, O> instead.
+pub struct LowerPartialFunction<'a, B, O, L = ()>
 where
     B: PartialOrd,
 {
-    funcs: Vec<LowerBoundedFunction<B, O>>,
+    funcs: Vec<LowerBoundedFunction<'a, B, O>>,
+    /// Label of each segment, aligned with funcs.
+    labels: Vec<L>,
 }
 
-impl<B, O> LowerPartialFunction<B, O>
+impl<'a, B, O> LowerPartialFunction<'a, B, O>
 where
     B: PartialOrd,
 {
     /// Creates a new LowerPartialFunctionBuilder.
-    pub fn new() -> LowerPartialFunctionBuilder<B, O> {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> LowerPartialFunctionBuilder<'a, B, O> {
         LowerPartialFunctionBuilder::new()
     }
 
+    /// Creates a function from segments sorted by strictly increasing lower bound,
+    /// validating them in a single pass.
+    /// Returns an error if a lower bound is undefined, or if it is not greater than the
+    /// previous one, which includes segments out of order.
+    pub fn from_sorted_segments(
+        segments: Vec<LowerBoundedFunction<'a, B, O>>,
+    ) -> Result<Self, InsertError<B>>
+    where
+        B: Clone,
+    {
+        for (i, b) in segments.iter().enumerate() {
+            if is_undefined(&b.lower) {
+                return Err(InsertError::NaNBound(NaNBoundError));
+            }
+            if i > 0 && b.lower <= segments[i - 1].lower {
+                return Err(InsertError::Overlap(OverlapError {
+                    first: i - 1,
+                    second: i,
+                    at: b.lower.clone(),
+                }));
+            }
+        }
+        Ok(LowerPartialFunction::from_sorted_segments_unchecked(
+            segments,
+        ))
+    }
+
+    /// Creates a function from segments without validating them.
+    /// The segments must be sorted by strictly increasing lower bound, as from_sorted_segments
+    /// checks. Otherwise the function is still safe to use but eval can give wrong results.
+    pub fn from_sorted_segments_unchecked(segments: Vec<LowerBoundedFunction<'a, B, O>>) -> Self {
+        let labels = vec![(); segments.len()];
+        LowerPartialFunction {
+            funcs: segments,
+            labels,
+        }
+    }
+}
+
+impl<'a, B, O, L> LowerPartialFunction<'a, B, O, L>
+where
+    B: PartialOrd,
+{
+    /// Creates a new LowerPartialFunctionBuilder for segments labeled with L.
+    pub fn new_labeled() -> LowerPartialFunctionBuilder<'a, B, O, L> {
+        LowerPartialFunctionBuilder::new()
+    }
+
+    /// Returns the number of segments.
+    pub fn len(&self) -> usize {
+        self.funcs.len()
+    }
+
+    /// Checks if the function has no segment, in which case it is undefined everywhere.
+    pub fn is_empty(&self) -> bool {
+        self.funcs.is_empty()
+    }
+
+    /// Returns the lowest lower bound, below which the function is undefined.
+    pub fn first_lower(&self) -> Option<&B> {
+        self.funcs.first().map(|b| &b.lower)
+    }
+
+    /// Returns the highest lower bound, from which the last segment is used.
+    pub fn last_lower(&self) -> Option<&B> {
+        self.funcs.last().map(|b| &b.lower)
+    }
+
     /// Evaluates the partial function.
     /// Returns None if no function is defined for the searched invariable value (x).
     pub fn eval(&self, x: B) -> Option<O> {
+        self.eval_indexed(x).map(|(_, y)| y)
+    }
+
+    /// Evaluates the partial function at a value converted to B, like an i32 for f64 bounds.
+    pub fn eval_into(&self, x: impl Into<B>) -> Option<O> {
+        self.eval(x.into())
+    }
+
+    /// Evaluates the partial function, also returning the index of the segment used
+    /// in the segments sorted by lower bound.
+    /// Returns None if no function is defined for the searched invariable value (x).
+    pub fn eval_indexed(&self, x: B) -> Option<(usize, O)> {
+        let i = self.index_of(&x)?;
+        let f = &self.funcs[i].func;
+        Some((i, f(x)))
+    }
+
+    /// Evaluates the partial function, also returning the label of the segment used.
+    /// Returns None if no function is defined for the searched invariable value (x).
+    pub fn eval_labeled(&self, x: B) -> Option<(&L, O)> {
+        let (i, y) = self.eval_indexed(x)?;
+        Some((&self.labels[i], y))
+    }
+
+    /// Returns the label of the segment that would be used to evaluate x, without evaluating it.
+    /// Returns None if no function is defined for the searched invariable value (x).
+    pub fn label_at(&self, x: &B) -> Option<&L> {
+        let i = self.index_of(x)?;
+        Some(&self.labels[i])
+    }
+
+    /// Returns the lower bound of the segment that would be used to evaluate x, without evaluating it.
+    /// Returns None if no function is defined for the searched invariable value (x).
+    pub fn segment_at(&self, x: &B) -> Option<&B> {
+        let i = self.index_of(x)?;
+        Some(&self.funcs[i].lower)
+    }
+
+    /// Evaluates the partial function, comparing bounds with cmp instead of partial_cmp.
+    /// cmp must be the comparator used with build_by.
+    /// Returns None if no function is defined for the searched invariable value (x).
+    pub fn eval_by(&self, x: B, cmp: impl Fn(&B, &B) -> Ordering) -> Option<O> {
+        let i = self.index_by(&x, &|a: &B, b: &B| Some(cmp(a, b)))?;
+        let f = &self.funcs[i].func;
+        Some(f(x))
+    }
+
+    /// Finds the index of the segment to use for x.
+    fn index_of(&self, x: &B) -> Option<usize> {
+        self.index_by(x, &B::partial_cmp)
+    }
+
+    /// Finds the index of the segment to use for x, comparing values with cmp.
+    fn index_by<C>(&self, x: &B, cmp: &C) -> Option<usize>
+    where
+        C: Fn(&B, &B) -> Option<Ordering>,
+    {
         let iter = self.funcs.iter().enumerate();
         for (i, bounded) in iter {
             let next = self.funcs.get(i + 1);
-            if x >= bounded.lower && ((next.is_some() && next.unwrap().lower > x) || next.is_none())
+            let reached = matches!(
+                cmp(x, &bounded.lower),
+                Some(Ordering::Greater) | Some(Ordering::Equal)
+            );
+            if reached
+                && ((next.is_some() && cmp(&next.unwrap().lower, x) == Some(Ordering::Greater))
+                    || next.is_none())
             {
-                let f = &bounded.func;
-                return Some(f(x));
+                return Some(i);
             }
         }
         None
     }
 }
 
+impl<'a, B: PartialOrd + Clone, O, L> LowerPartialFunction<'a, B, O, L> {
+    /// Evaluates the partial function, telling why it isn't defined at x if it isn't.
+    /// The value is defined exactly where eval returns Some.
+    pub fn try_eval(&self, x: B) -> Result<O, EvalError<B>> {
+        if is_undefined(&x) {
+            return Err(EvalError::NaN);
+        }
+        let min = &self.funcs.first().ok_or(EvalError::Empty)?.lower;
+        if &x < min {
+            return Err(EvalError::BelowDomain { min: min.clone() });
+        }
+        self.eval(x).ok_or(EvalError::Incomparable)
+    }
+
+    /// Turns the function back into a builder holding its segments and labels, so more can
+    /// be added before building it again.
+    /// Segments hidden by a segment of higher priority are dropped, the others having a
+    /// priority of 0.
+    pub fn into_builder(self) -> LowerPartialFunctionBuilder<'a, B, O, L> {
+        // Segments sharing a lower bound are sorted by priority, the last one being used.
+        let next_lowers = self
+            .funcs
+            .iter()
+            .skip(1)
+            .map(|b| Some(b.lower.clone()))
+            .chain(std::iter::once(None))
+            .collect::<Vec<_>>();
+        let (funcs, labels): (Vec<_>, Vec<_>) = self
+            .funcs
+            .into_iter()
+            .zip(self.labels)
+            .zip(next_lowers)
+            .filter(|((b, _), next)| next.as_ref() != Some(&b.lower))
+            .map(|(segment, _)| segment)
+            .unzip();
+        let priorities = vec![0; funcs.len()];
+        LowerPartialFunctionBuilder {
+            funcs,
+            labels,
+            priorities,
+        }
+    }
+
+    /// Takes the segments of the function, sorted by lower bound.
+    /// Iterating over the function gives the same segments, and both drop the labels.
+    pub fn into_segments(self) -> Vec<LowerBoundedFunction<'a, B, O>> {
+        self.funcs
+    }
+
+    /// Evaluates the partial function, also returning the lower bound of the segment used.
+    /// Returns None if no function is defined for the searched invariable value (x).
+    pub fn eval_with_bound(&self, x: B) -> Option<(B, O)> {
+        let i = self.index_of(&x)?;
+        let bounded = &self.funcs[i];
+        let f = &bounded.func;
+        Some((bounded.lower.clone(), f(x)))
+    }
+
+    /// Evaluates the partial function for each value of xs, which must be sorted in increasing order.
+    /// Walks the segments and the values together instead of searching the segments for each value.
+    pub fn eval_sorted(&self, xs: &[B]) -> Vec<Option<O>> {
+        debug_assert!(xs.windows(2).all(|w| w[0] <= w[1]));
+        let mut i = 0;
+        xs.iter()
+            .map(|x| {
+                while i + 1 < self.funcs.len() && x >= &self.funcs[i + 1].lower {
+                    i += 1;
+                }
+                match self.funcs.get(i) {
+                    Some(bounded) if x >= &bounded.lower => {
+                        let f = &bounded.func;
+                        Some(f(x.clone()))
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+}
+
+impl<'a, B: Ord, O, L> LowerPartialFunction<'a, B, O, L> {
+    /// Evaluates the partial function like eval, binary searching the segment instead of
+    /// scanning them, which is faster with many segments.
+    /// Requires totally ordered bounds like integers.
+    /// Returns None if no function is defined for the searched invariable value (x).
+    pub fn eval_ord(&self, x: B) -> Option<O> {
+        let reached = self.funcs.partition_point(|b| b.lower <= x);
+        let f = &self.funcs[reached.checked_sub(1)?].func;
+        Some(f(x))
+    }
+}
+
 /// A builder to create an immutable PartialFunction.
 #[derive(new)]
-pub struct LowerPartialFunctionBuilder<B, O> {
+pub struct LowerPartialFunctionBuilder<'a, B, O, L = ()> {
+    #[new(default)]
+    funcs: Vec<LowerBoundedFunction<'a, B, O>>,
+    /// Label of each segment, aligned with funcs.
     #[new(default)]
-    funcs: Vec<LowerBoundedFunction<B, O>>,
+    labels: Vec<L>,
+    /// Priority of each segment, aligned with funcs.
+    #[new(default)]
+    priorities: Vec<i32>,
 }
 
-impl<B: PartialOrd, O> LowerPartialFunctionBuilder<B, O> {
+impl<'a, B: PartialOrd, O, L: Default> LowerPartialFunctionBuilder<'a, B, O, L> {
     /// Adds a bounded function bounded between [lower,higher[ of function func.
-    pub fn with(mut self, lower: B, func: Box<dyn Fn(B) -> O>) -> Self {
-        debug_assert!(self.can_insert(&lower));
+    /// Its label is the default value of L.
+    pub fn with(mut self, lower: B, func: Box<dyn Fn(B) -> O + 'a>) -> Self {
+        self.insert(lower, None, L::default(), func);
+        self
+    }
+
+    /// Adds a function starting at lower, replacing the function of the segment added
+    /// using with that already starts there, if any.
+    ///
+    /// The replaced segment keeps its label. Otherwise this behaves like with, so the last
+    /// call for a given lower bound wins. Segments added using with_priority are kept.
+    pub fn with_replace(mut self, lower: B, func: Box<dyn Fn(B) -> O + 'a>) -> Self {
+        let existing = self
+            .funcs
+            .iter()
+            .zip(&self.priorities)
+            .position(|(b, &p)| b.lower == lower && p == 0);
+        match existing {
+            Some(i) => self.funcs[i].func = func,
+            // Segments of other priorities can start at lower too.
+            None => self.insert(lower, Some(0), L::default(), func),
+        }
+        self
+    }
+
+    /// Adds a function starting at lower, which can share its lower bound with segments
+    /// of other priorities.
+    ///
+    /// Where segments share a lower bound, eval uses the one with the highest priority
+    /// until the next lower bound.
+    /// Segments added using with have a priority of 0 and can't share their lower bound.
+    pub fn with_priority(
+        mut self,
+        lower: B,
+        priority: i32,
+        func: Box<dyn Fn(B) -> O + 'a>,
+    ) -> Self {
+        self.insert(lower, Some(priority), L::default(), func);
+        self
+    }
+
+    /// Adds each (lower, func) of iter, like with.
+    pub fn extend<I>(mut self, iter: I) -> Self
+    where
+        I: IntoIterator<Item = (B, Box<dyn Fn(B) -> O + 'a>)>,
+    {
+        Extend::extend(&mut self, iter);
+        self
+    }
+
+    /// Adds a segment starting at lower always returning value.
+    pub fn with_const(self, lower: B, value: O) -> Self
+    where
+        O: Clone + 'a,
+    {
+        self.with(lower, Box::new(move |_| value.clone()))
+    }
+
+    /// Adds all the segments of iter, validating them together with a single sort instead of
+    /// checking each of them against all the others like with.
+    ///
+    /// The segments are added sorted by lower bound, like PartialFunctionBuilder::with_many.
+    /// Returns every problem found, in which case nothing is added. Segments are reported by
+    /// their insertion index as if they were added in the order of iter.
+    pub fn with_many<I>(mut self, iter: I) -> Result<Self, BuildError<B>>
+    where
+        I: IntoIterator<Item = (B, Box<dyn Fn(B) -> O + 'a>)>,
+        B: Clone,
+    {
+        let start = self.funcs.len();
+        let mut added = iter
+            .into_iter()
+            .enumerate()
+            .map(|(i, (lower, func))| (start + i, LowerBoundedFunction::new(func, lower)))
+            .collect::<Vec<_>>();
+        let mut errors = added
+            .iter()
+            .filter(|(_, b)| is_undefined(&b.lower))
+            .map(|_| InsertError::NaNBound(NaNBoundError))
+            .collect::<Vec<_>>();
+        let by_lower = |a: &B, b: &B| a.partial_cmp(b).unwrap_or(Ordering::Equal);
+        added.sort_by(|(_, a), (_, b)| by_lower(&a.lower, &b.lower));
+        if errors.is_empty() {
+            let mut all = self
+                .funcs
+                .iter()
+                .enumerate()
+                .chain(added.iter().map(|(i, b)| (*i, b)))
+                .collect::<Vec<_>>();
+            all.sort_by(|(_, a), (_, b)| by_lower(&a.lower, &b.lower));
+            for w in all.windows(2) {
+                let ((first, a), (second, b)) = (w[0], w[1]);
+                // Existing segments can share a lower bound through their priorities.
+                if a.lower == b.lower && (first >= start || second >= start) {
+                    errors.push(InsertError::Overlap(OverlapError {
+                        first: first.min(second),
+                        second: first.max(second),
+                        at: b.lower.clone(),
+                    }));
+                }
+            }
+        }
+        if !errors.is_empty() {
+            return Err(BuildError { errors });
+        }
+        for (_, b) in added {
+            self.funcs.push(b);
+            self.labels.push(L::default());
+            self.priorities.push(0);
+        }
+        Ok(self)
+    }
+}
+
+impl<'a, B: PartialOrd, O, L> LowerPartialFunctionBuilder<'a, B, O, L> {
+    /// Adds a function starting at lower, labeled with label.
+    pub fn with_labeled(mut self, lower: B, label: L, func: Box<dyn Fn(B) -> O + 'a>) -> Self {
+        self.insert(lower, None, label, func);
+        self
+    }
+
+    /// Adds a segment, with the same checks as with_priority if a priority is given
+    /// or as with otherwise.
+    fn insert(
+        &mut self,
+        lower: B,
+        priority: Option<i32>,
+        label: L,
+        func: Box<dyn Fn(B) -> O + 'a>,
+    ) {
+        debug_assert!(!is_undefined(&lower), "bound of a segment can't be NaN");
+        let priority = match priority {
+            Some(priority) => {
+                debug_assert!(self.can_insert_priority(&lower, priority));
+                priority
+            }
+            None => {
+                debug_assert!(self.can_insert(&lower));
+                0
+            }
+        };
         let f = LowerBoundedFunction { func, lower };
         self.funcs.push(f);
-        self
+        self.labels.push(label);
+        self.priorities.push(priority);
+    }
+
+    /// Returns true if a segment starting at lower was already added.
+    pub fn contains_lower(&self, lower: &B) -> bool {
+        self.funcs.iter().any(|b| lower == &b.lower)
     }
 
     /// Check if you can safely insert into the function list for the specified bounds.
+    /// Bounds that can't be compared are rejected.
     pub fn can_insert(&self, lower: &B) -> bool {
-        !self.funcs.iter().any(|b| lower == &b.lower)
+        !is_undefined(lower) && !self.funcs.iter().any(|b| lower == &b.lower)
+    }
+
+    /// Check if you can safely insert a segment of this priority starting at lower,
+    /// which is the case if no segment of the same priority starts there.
+    pub fn can_insert_priority(&self, lower: &B, priority: i32) -> bool {
+        !is_undefined(lower)
+            && !self
+                .funcs
+                .iter()
+                .zip(&self.priorities)
+                .any(|(b, &p)| lower == &b.lower && p == priority)
+    }
+
+    /// Adds a function starting at lower.
+    /// Returns an error instead of inserting if can_insert would return false.
+    pub fn try_with(self, lower: B, func: Box<dyn Fn(B) -> O + 'a>) -> Result<Self, InsertError<B>>
+    where
+        B: Clone,
+        L: Default,
+    {
+        if is_undefined(&lower) {
+            return Err(InsertError::NaNBound(NaNBoundError));
+        }
+        if let Some(first) = self.funcs.iter().position(|b| b.lower == lower) {
+            return Err(InsertError::Overlap(OverlapError {
+                first,
+                second: self.funcs.len(),
+                at: lower,
+            }));
+        }
+        Ok(self.with(lower, func))
+    }
+
+    /// Checks that no two segments of the same priority share a lower bound, reporting the
+    /// first conflict in insertion order.
+    /// If this succeeds, build will succeed too.
+    pub fn validate(&self) -> Result<(), OverlapError<B>>
+    where
+        B: Clone,
+    {
+        for (second, b) in self.funcs.iter().enumerate() {
+            let priority = self.priorities[second];
+            let conflict = self.funcs[..second]
+                .iter()
+                .zip(&self.priorities)
+                .position(|(a, &p)| a.lower == b.lower && p == priority);
+            if let Some(first) = conflict {
+                return Err(OverlapError {
+                    first,
+                    second,
+                    at: b.lower.clone(),
+                });
+            }
+        }
+        Ok(())
     }
 
     /// Builds the PartialFunction from the functions added using with.
-    pub fn build(mut self) -> LowerPartialFunction<B, O> {
+    pub fn build(self) -> LowerPartialFunction<'a, B, O, L> {
+        self.build_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+    }
+
+    /// Builds the LowerPartialFunction, sorting the segments with cmp instead of partial_cmp.
+    /// Useful for bounds where partial_cmp doesn't give a total order.
+    /// The resulting function must be evaluated using eval_by with the same comparator.
+    pub fn build_by(self, cmp: impl Fn(&B, &B) -> Ordering) -> LowerPartialFunction<'a, B, O, L> {
+        // Segments sharing a lower bound are sorted by priority, the last one being used.
+        let mut segments = self
+            .funcs
+            .into_iter()
+            .zip(self.labels.into_iter().zip(self.priorities))
+            .collect::<Vec<_>>();
+        segments
+            .sort_by(|(a, (_, pa)), (b, (_, pb))| cmp(&a.lower, &b.lower).then_with(|| pa.cmp(pb)));
+        let (funcs, labels) = segments
+            .into_iter()
+            .map(|(f, (label, _))| (f, label))
+            .unzip();
+        LowerPartialFunction { funcs, labels }
+    }
+}
+
+impl<'a, B: PartialOrd, O, L> IntoIterator for LowerPartialFunction<'a, B, O, L> {
+    type Item = LowerBoundedFunction<'a, B, O>;
+    type IntoIter = std::vec::IntoIter<LowerBoundedFunction<'a, B, O>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.funcs.into_iter()
+    }
+}
+
+/// An empty function, undefined everywhere.
+impl<'a, B: PartialOrd, O, L> Default for LowerPartialFunction<'a, B, O, L> {
+    fn default() -> Self {
+        LowerPartialFunction {
+            funcs: vec![],
+            labels: vec![],
+        }
+    }
+}
+
+impl<'a, B, O, L> Default for LowerPartialFunctionBuilder<'a, B, O, L> {
+    fn default() -> Self {
+        LowerPartialFunctionBuilder::new()
+    }
+}
+
+/// Adds segments as (lower, func), like with.
+impl<'a, B: PartialOrd, O, L: Default> Extend<(B, Box<dyn Fn(B) -> O + 'a>)>
+    for LowerPartialFunctionBuilder<'a, B, O, L>
+{
+    fn extend<I: IntoIterator<Item = (B, Box<dyn Fn(B) -> O + 'a>)>>(&mut self, iter: I) {
+        for (lower, func) in iter {
+            self.insert(lower, None, L::default(), func);
+        }
+    }
+}
+
+impl<'a, B: PartialOrd, O, L: Default> FromIterator<(B, Box<dyn Fn(B) -> O + 'a>)>
+    for LowerPartialFunctionBuilder<'a, B, O, L>
+{
+    fn from_iter<I: IntoIterator<Item = (B, Box<dyn Fn(B) -> O + 'a>)>>(iter: I) -> Self {
+        LowerPartialFunctionBuilder::new().extend(iter)
+    }
+}
+
+/// An upper bounded function is a function that is valid from ]-infinite..x], or from where another function ends.
+struct UpperBoundedFunction<'a, B, O> {
+    /// The stored function f(x) = ???
+    pub func: Box<dyn Fn(B) -> O + 'a>,
+    /// The higher bound of the function.
+    pub higher: B,
+}
+
+/// An upper partial function is a function that is defined by segments valid from ]-infinite..x], or from where another function ends.
+/// It is the mirror of LowerPartialFunction: it takes the first function whose higher bound is not below x.
+///
+/// Example:
+/// ]-infinity..0] = 5
+/// ]-infinity..1] = 10
+///
+/// f(-70) = 5
+/// f(0) = 5
+/// f(0.5) = 10
+/// f(1) = 10
+/// f(2) = None
+pub struct UpperPartialFunction<'a, B, O>
+where
+    B: PartialOrd,
+{
+    funcs: Vec<UpperBoundedFunction<'a, B, O>>,
+}
+
+impl<'a, B, O> UpperPartialFunction<'a, B, O>
+where
+    B: PartialOrd,
+{
+    /// Creates a new UpperPartialFunctionBuilder.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> UpperPartialFunctionBuilder<'a, B, O> {
+        UpperPartialFunctionBuilder::new()
+    }
+
+    /// Evaluates the partial function.
+    /// Returns None if no function is defined for the searched invariable value (x).
+    pub fn eval(&self, x: B) -> Option<O> {
+        let bounded = self.funcs.iter().find(|b| x <= b.higher)?;
+        let f = &bounded.func;
+        Some(f(x))
+    }
+}
+
+/// A builder to create an immutable UpperPartialFunction.
+#[derive(new)]
+pub struct UpperPartialFunctionBuilder<'a, B, O> {
+    #[new(default)]
+    funcs: Vec<UpperBoundedFunction<'a, B, O>>,
+}
+
+impl<'a, B: PartialOrd, O> UpperPartialFunctionBuilder<'a, B, O> {
+    /// Adds a function ending at higher, included.
+    pub fn with(mut self, higher: B, func: Box<dyn Fn(B) -> O + 'a>) -> Self {
+        debug_assert!(!is_undefined(&higher), "bound of a segment can't be NaN");
+        debug_assert!(self.can_insert(&higher));
+        self.funcs.push(UpperBoundedFunction { func, higher });
+        self
+    }
+
+    /// Check if you can safely insert into the function list for the specified bound.
+    /// Bounds that can't be compared are rejected.
+    pub fn can_insert(&self, higher: &B) -> bool {
+        !is_undefined(higher) && !self.funcs.iter().any(|b| higher == &b.higher)
+    }
+
+    /// Builds the UpperPartialFunction from the functions added using with.
+    pub fn build(mut self) -> UpperPartialFunction<'a, B, O> {
         self.funcs
-            .sort_by(|a, b| a.lower.partial_cmp(&b.lower).unwrap_or(Ordering::Equal));
-        LowerPartialFunction { funcs: self.funcs }
+            .sort_by(|a, b| a.higher.partial_cmp(&b.higher).unwrap_or(Ordering::Equal));
+        UpperPartialFunction { funcs: self.funcs }
     }
 }
 
 /// More convenient syntax to create a partial function
+///
+/// Bounds can be any expression and are evaluated once, in order, when building.
+/// Prefix the variable of an arm with move to move the values it captures into its closure.
 #[macro_export]
 macro_rules! partfn {
-    ( $( [$start:expr, $end:expr]: $var:ident -> $f:expr,)* ) => {
+    (@arms $func:ident;) => {};
+    (@arms $func:ident; [$start:expr, $end:expr]: move $var:ident -> $f:expr, $($rest:tt)*) => {
+        $func = $func.with($start, $end, Box::new(move |$var| $f));
+        $crate::partfn!(@arms $func; $($rest)*);
+    };
+    (@arms $func:ident; [$start:expr, $end:expr]: $var:ident -> $f:expr, $($rest:tt)*) => {
+        $func = $func.with($start, $end, Box::new(|$var| $f));
+        $crate::partfn!(@arms $func; $($rest)*);
+    };
+    ( $($arms:tt)* ) => {
         {
             let mut func = PartialFunction::new();
-            $( func = func.with($start, $end, Box::new(|$var| $f)); )*
+            $crate::partfn!(@arms func; $($arms)*);
             func.build()
         }
     };
 }
 
 /// More convenient syntax to create a lower partial function
+///
+/// Bounds and arms work like in partfn.
 #[macro_export]
 macro_rules! lowpartfn {
-    ( $( [$bound:expr]: $var:ident -> $f:expr,)* ) => {
+    (@arms $func:ident;) => {};
+    (@arms $func:ident; [$bound:expr]: move $var:ident -> $f:expr, $($rest:tt)*) => {
+        $func = $func.with($bound, Box::new(move |$var| $f));
+        $crate::lowpartfn!(@arms $func; $($rest)*);
+    };
+    (@arms $func:ident; [$bound:expr]: $var:ident -> $f:expr, $($rest:tt)*) => {
+        $func = $func.with($bound, Box::new(|$var| $f));
+        $crate::lowpartfn!(@arms $func; $($rest)*);
+    };
+    ( $($arms:tt)* ) => {
         {
             let mut func = LowerPartialFunction::new();
-            $( func = func.with($bound, Box::new(|$var| $f)); )*
+            $crate::lowpartfn!(@arms func; $($arms)*);
+            func.build()
+        }
+    };
+}
+
+/// More convenient syntax to create an upper partial function
+///
+/// Bounds and arms work like in partfn.
+#[macro_export]
+macro_rules! uppartfn {
+    (@arms $func:ident;) => {};
+    (@arms $func:ident; [$bound:expr]: move $var:ident -> $f:expr, $($rest:tt)*) => {
+        $func = $func.with($bound, Box::new(move |$var| $f));
+        $crate::uppartfn!(@arms $func; $($rest)*);
+    };
+    (@arms $func:ident; [$bound:expr]: $var:ident -> $f:expr, $($rest:tt)*) => {
+        $func = $func.with($bound, Box::new(|$var| $f));
+        $crate::uppartfn!(@arms $func; $($rest)*);
+    };
+    ( $($arms:tt)* ) => {
+        {
+            let mut func = UpperPartialFunction::new();
+            $crate::uppartfn!(@arms func; $($arms)*);
+            func.build()
+        }
+    };
+}
+
+/// More convenient syntax to create a discrete partial function
+///
+/// Keys and arms work like in partfn.
+#[macro_export]
+macro_rules! discretefn {
+    (@arms $func:ident;) => {};
+    (@arms $func:ident; [$key:expr]: move $var:ident -> $f:expr, $($rest:tt)*) => {
+        $func = $func.with($key, Box::new(move |$var| $f));
+        $crate::discretefn!(@arms $func; $($rest)*);
+    };
+    (@arms $func:ident; [$key:expr]: $var:ident -> $f:expr, $($rest:tt)*) => {
+        $func = $func.with($key, Box::new(|$var| $f));
+        $crate::discretefn!(@arms $func; $($rest)*);
+    };
+    ( $($arms:tt)* ) => {
+        {
+            let mut func = DiscretePartialFunction::new();
+            $crate::discretefn!(@arms func; $($arms)*);
             func.build()
         }
     };
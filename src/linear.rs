@@ -0,0 +1,158 @@
+use crate::PartialFunction;
+use std::error::Error;
+use std::fmt;
+
+/// Returned when a function expected to be strictly monotonic is not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonMonotonicError {
+    /// Index of the segment where monotonicity is violated.
+    pub segment: usize,
+}
+
+impl fmt::Display for NonMonotonicError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "function is not strictly monotonic at segment {}",
+            self.segment
+        )
+    }
+}
+
+impl Error for NonMonotonicError {}
+
+/// Creates the line going through (x0, y0) and (x1, y1).
+fn lerp(x0: f64, y0: f64, x1: f64, y1: f64) -> Box<dyn Fn(f64) -> f64> {
+    let slope = (y1 - y0) / (x1 - x0);
+    Box::new(move |x| y0 + (x - x0) * slope)
+}
+
+impl<'a> PartialFunction<'a, f64, f64> {
+    /// Creates a piecewise-linear function going through the given (x, y) control points.
+    /// One segment is created per consecutive pair of points, so the function is defined
+    /// from the first to the last x.
+    /// The points must be sorted by strictly increasing x.
+    pub fn linear(points: &[(f64, f64)]) -> PartialFunction<'a, f64, f64> {
+        debug_assert!(points.windows(2).all(|w| w[0].0 < w[1].0));
+        points
+            .windows(2)
+            .fold(PartialFunction::new(), |func, w| {
+                let ((x0, y0), (x1, y1)) = (w[0], w[1]);
+                func.with(x0, x1, lerp(x0, y0, x1, y1))
+            })
+            .build()
+    }
+
+    /// Approximates f over [a, b] with n equal-width constant segments.
+    ///
+    /// f is evaluated once per segment, at its midpoint, so the result is cheap to evaluate
+    /// repeatedly even when f is not.
+    /// Returns an empty function if n is 0.
+    pub fn from_fn(
+        a: f64,
+        b: f64,
+        n: usize,
+        f: impl Fn(f64) -> f64,
+    ) -> PartialFunction<'a, f64, f64> {
+        debug_assert!(a < b);
+        let bound = |i: usize| {
+            if i == n {
+                b
+            } else {
+                a + (b - a) * i as f64 / n as f64
+            }
+        };
+        (0..n)
+            .fold(PartialFunction::new(), |func, i| {
+                let (lower, higher) = (bound(i), bound(i + 1));
+                let value = f(lower + (higher - lower) * 0.5);
+                func.with(lower, higher, Box::new(move |_| value))
+            })
+            .build()
+    }
+
+    /// Creates the integral of the function from the start of its domain up to x.
+    ///
+    /// The integral is computed with the trapezoidal rule using `steps` subdivisions per
+    /// segment, or per piece of a segment cut by overlapping ones, and is linearly interpolated between those points.
+    /// Gaps between segments add nothing to the integral and stay undefined.
+    /// Overlapping segments are integrated where they take precedence, like eval uses them.
+    pub fn antiderivative(&self, steps: usize) -> PartialFunction<'a, f64, f64> {
+        let steps = steps.max(1);
+        let mut total = 0.0;
+        let mut result = PartialFunction::new();
+        for (lower, higher, i) in self.segment_pieces() {
+            let f = &self.funcs[i];
+            if lower == higher {
+                let value = total;
+                result = result.with_point(lower, Box::new(move |_| value));
+                continue;
+            }
+            let width = (higher - lower) / steps as f64;
+            let mut x0 = lower;
+            let mut y0 = f(x0);
+            for k in 1..=steps {
+                let x1 = if k == steps {
+                    higher
+                } else {
+                    lower + width * k as f64
+                };
+                let y1 = f(x1);
+                let area = (y0 + y1) * 0.5 * (x1 - x0);
+                result = result.with(x0, x1, lerp(x0, total, x1, total + area));
+                total += area;
+                x0 = x1;
+                y0 = y1;
+            }
+        }
+        result.build()
+    }
+
+    /// Inverts a strictly monotonic piecewise-linear function.
+    ///
+    /// Each segment is assumed to be linear: it is only evaluated at its bounds
+    /// and the inverse is interpolated between those control points.
+    /// Overlapping segments are cut where another one takes precedence, like eval uses them.
+    /// Returns an error if the control points are not strictly increasing or strictly decreasing.
+    pub fn invert_linear(&self) -> Result<PartialFunction<'a, f64, f64>, NonMonotonicError> {
+        let pieces = self.segment_pieces();
+        let points = pieces
+            .iter()
+            .map(|&(lower, higher, i)| {
+                let f = &self.funcs[i];
+                (lower, f(lower), higher, f(higher))
+            })
+            .collect::<Vec<_>>();
+        let increasing = points.first().is_none_or(|p| p.3 > p.1);
+        let rises = |from: f64, to: f64| {
+            if increasing {
+                to > from
+            } else {
+                to < from
+            }
+        };
+        for (k, p) in points.iter().enumerate() {
+            let segment = pieces[k].2;
+            if !rises(p.1, p.3) {
+                return Err(NonMonotonicError { segment });
+            }
+            if k > 0 {
+                let prev = points[k - 1];
+                // Touching segments may share their output at the junction.
+                let continuous = prev.2 == p.0 && prev.3 == p.1;
+                if !continuous && !rises(prev.3, p.1) {
+                    return Err(NonMonotonicError { segment });
+                }
+            }
+        }
+        let mut inverse = PartialFunction::new();
+        for (x0, y0, x1, y1) in points {
+            inverse = if increasing {
+                inverse.with(y0, y1, lerp(y0, x0, y1, x1))
+            } else {
+                inverse.with(y1, y0, lerp(y1, x1, y0, x0))
+            };
+        }
+        Ok(inverse.build())
+    }
+}
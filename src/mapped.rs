@@ -0,0 +1,44 @@
+use crate::{LowerPartialFunction, PartialFunction};
+
+/// A partial function evaluated through a conversion of its input.
+/// See PartialFunction::map_domain.
+///
+/// The bounds stay in the space of the original function and the conversion can't be undone,
+/// so the wrapper only gives access to eval.
+pub struct MappedPartialFunction<'a, B2, O> {
+    func: Box<dyn Fn(B2) -> Option<O> + 'a>,
+}
+
+impl<'a, B: PartialOrd + 'a, O: 'a> PartialFunction<'a, B, O> {
+    /// Reparameterizes the function, evaluating it at forward(x2) for each x2.
+    /// Useful when a curve is authored in one unit but queried in another.
+    pub fn map_domain<B2>(
+        self,
+        forward: impl Fn(B2) -> B + 'a,
+    ) -> MappedPartialFunction<'a, B2, O> {
+        MappedPartialFunction {
+            func: Box::new(move |x2| self.eval(forward(x2))),
+        }
+    }
+}
+
+impl<'a, B: PartialOrd + 'a, O: 'a, L: 'a> LowerPartialFunction<'a, B, O, L> {
+    /// Reparameterizes the function, evaluating it at forward(x2) for each x2.
+    /// See PartialFunction::map_domain.
+    pub fn map_domain<B2>(
+        self,
+        forward: impl Fn(B2) -> B + 'a,
+    ) -> MappedPartialFunction<'a, B2, O> {
+        MappedPartialFunction {
+            func: Box::new(move |x2| self.eval(forward(x2))),
+        }
+    }
+}
+
+impl<'a, B2, O> MappedPartialFunction<'a, B2, O> {
+    /// Converts x2 and evaluates the original function at the result.
+    /// Returns None if the original function is not defined there.
+    pub fn eval(&self, x2: B2) -> Option<O> {
+        (self.func)(x2)
+    }
+}
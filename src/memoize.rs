@@ -0,0 +1,97 @@
+use crate::PartialFunction;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+/// A partial function caching the result of each evaluation.
+/// See PartialFunction::memoized.
+///
+/// Keys must be Eq and Hash, so this targets integer or otherwise ordered-key domains.
+/// Floats can be used through a wrapper implementing those traits on their bits,
+/// keeping in mind that values differing by any amount are cached separately.
+pub struct Memoized<'a, B, O> {
+    func: PartialFunction<'a, B, O>,
+    cache: RefCell<Cache<B, O>>,
+}
+
+/// Another name for Memoized.
+pub type MemoizedPartialFunction<'a, B, O> = Memoized<'a, B, O>;
+
+/// The cached results, with their insertion order to know which ones are the oldest.
+struct Cache<B, O> {
+    results: HashMap<B, Option<O>>,
+    order: VecDeque<B>,
+    max_size: Option<usize>,
+}
+
+impl<'a, B: PartialOrd + Eq + Hash + Clone, O: Clone> PartialFunction<'a, B, O> {
+    /// Caches the results of eval, for functions with expensive segments evaluated
+    /// repeatedly at the same values.
+    pub fn memoized(self) -> Memoized<'a, B, O> {
+        self.memoized_with_capacity(0)
+    }
+
+    /// Caches the results of eval, allocating room for capacity results upfront.
+    pub fn memoized_with_capacity(self, capacity: usize) -> Memoized<'a, B, O> {
+        Memoized {
+            func: self,
+            cache: RefCell::new(Cache {
+                results: HashMap::with_capacity(capacity),
+                order: VecDeque::new(),
+                max_size: None,
+            }),
+        }
+    }
+}
+
+impl<'a, B: PartialOrd + Eq + Hash + Clone, O: Clone> Memoized<'a, B, O> {
+    /// Limits the cache to max_size results, forgetting the oldest result when full.
+    /// Results already cached are kept, the oldest ones being forgotten if there are more
+    /// than max_size.
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        let cache = self.cache.get_mut();
+        cache.max_size = Some(max_size);
+        while cache.results.len() > max_size {
+            match cache.order.pop_front() {
+                Some(oldest) => cache.results.remove(&oldest),
+                None => break,
+            };
+        }
+        self
+    }
+
+    /// Evaluates the partial function, using the cached result if x was already evaluated.
+    /// Undefined results are cached too.
+    pub fn eval(&self, x: B) -> Option<O> {
+        if let Some(cached) = self.cache.borrow().results.get(&x) {
+            return cached.clone();
+        }
+        let result = self.func.eval(x.clone());
+        let mut cache = self.cache.borrow_mut();
+        if let Some(max_size) = cache.max_size {
+            if max_size == 0 {
+                return result;
+            }
+            if cache.results.len() >= max_size {
+                if let Some(oldest) = cache.order.pop_front() {
+                    cache.results.remove(&oldest);
+                }
+            }
+        }
+        cache.order.push_back(x.clone());
+        cache.results.insert(x, result.clone());
+        result
+    }
+
+    /// Returns the number of cached results.
+    pub fn cache_len(&self) -> usize {
+        self.cache.borrow().results.len()
+    }
+
+    /// Forgets all the cached results.
+    pub fn clear_cache(&mut self) {
+        let cache = self.cache.get_mut();
+        cache.results.clear();
+        cache.order.clear();
+    }
+}
@@ -0,0 +1,382 @@
+use crate::{Part, PartialFunction};
+use std::cmp::Ordering;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// A floating point number usable by the numeric helpers.
+/// Implemented for f32 and f64.
+pub trait Real:
+    Copy
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    /// Converts from a f64, losing precision if needed.
+    fn from_f64(v: f64) -> Self;
+    /// Converts to a f64.
+    fn to_f64(self) -> f64;
+    /// Returns the largest integer less than or equal to self.
+    fn floor(self) -> Self;
+}
+
+macro_rules! impl_real {
+    ($($t:ty),*) => {
+        $(
+            impl Real for $t {
+                fn from_f64(v: f64) -> Self {
+                    v as $t
+                }
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+                fn floor(self) -> Self {
+                    <$t>::floor(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_real!(f32, f64);
+
+impl<'a, B: Real, O: Real> PartialFunction<'a, B, O> {
+    /// Estimates the derivative at x by fitting a quadratic (Savitzky-Golay style)
+    /// to `steps` samples spread evenly over [x - window, x + window].
+    ///
+    /// Samples where the function is undefined are ignored, which makes this usable
+    /// at the edges of the domain.
+    /// A larger window smooths out more noise but also flattens real curvature,
+    /// while more steps only add precision to the fit at the cost of more evaluations.
+    /// Returns None if x is undefined or if less than two samples are defined.
+    pub fn derivative_smooth(&self, x: B, window: B, steps: usize) -> Option<O> {
+        self.eval(x)?;
+        let (xf, w) = (x.to_f64(), window.to_f64());
+        let samples = (0..steps)
+            .filter_map(|i| {
+                let t = if steps > 1 {
+                    -w + 2.0 * w * i as f64 / (steps - 1) as f64
+                } else {
+                    0.0
+                };
+                self.eval(B::from_f64(xf + t)).map(|y| (t, y.to_f64()))
+            })
+            .collect::<Vec<_>>();
+        if samples.len() < 2 {
+            return None;
+        }
+        // Moments of the samples, centered on x.
+        let mut s = [0.0; 5];
+        let mut sy = [0.0; 3];
+        for &(t, y) in &samples {
+            let mut p = 1.0;
+            for (k, m) in s.iter_mut().enumerate() {
+                *m += p;
+                if k < 3 {
+                    sy[k] += p * y;
+                }
+                p *= t;
+            }
+        }
+        let det3 = |m: [[f64; 3]; 3]| {
+            m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+                - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+                + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+        };
+        let normal = [[s[0], s[1], s[2]], [s[1], s[2], s[3]], [s[2], s[3], s[4]]];
+        let det = det3(normal);
+        let slope = if samples.len() >= 3 && det.abs() > f64::EPSILON {
+            // Cramer's rule for the linear coefficient of the quadratic fit.
+            let mut m = normal;
+            for (row, y) in m.iter_mut().zip(sy.iter()) {
+                row[1] = *y;
+            }
+            det3(m) / det
+        } else {
+            // Straight line fit.
+            (s[0] * sy[1] - s[1] * sy[0]) / (s[0] * s[2] - s[1] * s[1])
+        };
+        Some(O::from_f64(slope))
+    }
+}
+
+/// A piece of a range between two bounds, with the function used inside it if any.
+type Piece<'f, B, O> = (f64, f64, Option<&'f dyn Fn(B) -> O>);
+
+impl<'a, B: Real, O> PartialFunction<'a, B, O> {
+    /// Cuts [from, to] at every bound of the function, giving each piece with the function
+    /// used inside it, or None where the function is undefined.
+    /// from must be lower than to.
+    fn pieces(&self, from: f64, to: f64) -> Vec<Piece<'_, B, O>> {
+        let cuts = std::iter::once(from)
+            .chain(
+                self.breakpoints
+                    .iter()
+                    .map(|b| b.to_f64())
+                    .filter(|&b| from < b && b < to),
+            )
+            .chain(std::iter::once(to))
+            .collect::<Vec<_>>();
+        cuts.windows(2)
+            .map(|w| {
+                let (lower, higher) = (w[0], w[1]);
+                let f = self.func_at(&B::from_f64(lower + (higher - lower) * 0.5));
+                (lower, higher, f)
+            })
+            .collect()
+    }
+
+    /// Cuts the bounded segments at every bound of the function, giving each piece with the
+    /// index of the segment used inside it, sorted.
+    /// Where segments overlap, the piece uses the one taking precedence, as eval does.
+    /// A value only defined by a point segment gives a piece with equal bounds.
+    /// Gaps and unbounded segments are skipped.
+    pub(crate) fn segment_pieces(&self) -> Vec<(B, B, usize)> {
+        let segment = |x: &B| match self.part_of(x) {
+            Some(Part::Segment(i)) => Some(i),
+            _ => None,
+        };
+        let half = B::from_f64(0.5);
+        let mut pieces = vec![];
+        for (k, &lower) in self.breakpoints.iter().enumerate() {
+            let piece = self.breakpoints.get(k + 1).and_then(|&higher| {
+                let i = segment(&(lower + (higher - lower) * half))?;
+                Some((lower, higher, i))
+            });
+            match piece {
+                Some(piece) => pieces.push(piece),
+                None => {
+                    if let Some(i) = segment(&lower).filter(|&i| self.is_point(i)) {
+                        pieces.push((lower, lower, i));
+                    }
+                }
+            }
+        }
+        pieces
+    }
+}
+
+impl<'a, B: Real, O: Real> PartialFunction<'a, B, O> {
+    /// Estimates the derivative at x with a central difference over [x - h, x + h].
+    ///
+    /// Only the function of the segment containing x is used and the differences never cross
+    /// its bounds, so near a bound the difference becomes one-sided within the segment.
+    /// See derivative_smooth for noisy functions.
+    /// Returns None if x is undefined or falls on a point segment.
+    pub fn derivative_at(&self, x: B, h: B) -> Option<O> {
+        let (lower, higher) = match self.part_of(&x)? {
+            Part::Below => (None, Some(self.below.as_ref()?.bound)),
+            Part::Segment(i) => (Some(self.lowers[i]), Some(self.highers[i])),
+            Part::Above => (Some(self.above.as_ref()?.bound), None),
+        };
+        let f = self.func_at(&x)?;
+        let (xf, h) = (x.to_f64(), h.to_f64().abs());
+        let left = lower.map_or(xf - h, |l| (xf - h).max(l.to_f64()));
+        let right = higher.map_or(xf + h, |r| (xf + h).min(r.to_f64()));
+        if right <= left {
+            return None;
+        }
+        let y = |v: f64| f(B::from_f64(v)).to_f64();
+        Some(O::from_f64((y(right) - y(left)) / (right - left)))
+    }
+
+    /// Integrates the function from `from` to `to` with the trapezoidal rule.
+    ///
+    /// The range is cut at every segment bound and each piece is integrated separately
+    /// with `steps` subdivisions using the function of its segment, so discontinuities
+    /// between segments are kept sharp.
+    /// Integrating backward, with `to` lower than `from`, negates the result.
+    /// Returns None if the function is undefined anywhere in the range, gaps included.
+    pub fn integrate(&self, from: B, to: B, steps: usize) -> Option<O> {
+        if to < from {
+            return self.integrate(to, from, steps).map(|area| -area);
+        }
+        if from == to {
+            self.eval(from)?;
+            return Some(O::from_f64(0.0));
+        }
+        let steps = steps.max(1);
+        let mut total = 0.0;
+        for (lower, higher, f) in self.pieces(from.to_f64(), to.to_f64()) {
+            let f = f?;
+            let y = |x: f64| f(B::from_f64(x)).to_f64();
+            let width = (higher - lower) / steps as f64;
+            let inner = (1..steps).map(|k| y(lower + width * k as f64)).sum::<f64>();
+            total += width * ((y(lower) + y(higher)) * 0.5 + inner);
+        }
+        Some(O::from_f64(total))
+    }
+}
+
+impl<'a, B: Real, O: PartialOrd> PartialFunction<'a, B, O> {
+    /// Finds an input for which the function reaches target, within tolerance.
+    ///
+    /// The function must be monotonic on each segment. The segments are cut at every bound
+    /// and the pieces are searched in order, the first one whose outputs at its bounds bracket
+    /// the target being bisected with the function used inside it.
+    /// A target reached exactly at a bound is only found if the function is defined there.
+    /// Unbounded segments are not searched.
+    /// Returns None if no piece brackets the target.
+    pub fn find_input(&self, target: &O, tolerance: B) -> Option<B> {
+        let half = B::from_f64(0.5);
+        let reached = |x: B| self.eval(x).as_ref() == Some(target);
+        for (mut lo, mut hi, i) in self.segment_pieces() {
+            let f = &self.funcs[i];
+            let (y_lo, y_hi) = (f(lo), f(hi));
+            if &y_lo == target && reached(lo) {
+                return Some(lo);
+            }
+            if &y_hi == target {
+                if reached(hi) {
+                    return Some(hi);
+                }
+                continue;
+            }
+            let rising = y_lo < y_hi;
+            let brackets = if rising {
+                &y_lo < target && target < &y_hi
+            } else {
+                &y_hi < target && target < &y_lo
+            };
+            if !brackets {
+                continue;
+            }
+            loop {
+                let mid = lo + (hi - lo) * half;
+                if hi - lo <= tolerance {
+                    return Some(mid);
+                }
+                if mid <= lo || mid >= hi {
+                    // Out of precision.
+                    return Some(lo);
+                }
+                let y = f(mid);
+                if &y == target {
+                    return Some(mid);
+                }
+                if (&y < target) == rising {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the greatest output found by sampling the function over [from, to].
+    ///
+    /// Each piece of the range between two segment bounds is sampled at `steps + 1` evenly
+    /// spaced values, its bounds included. The bounds of a piece are evaluated like eval does,
+    /// so a higher bound excluded by its segment gives the output of the segment defined
+    /// there, if any.
+    /// The result is an output the function reaches, exact for extrema reached at a sampled
+    /// value, like the bounds of monotonic segments, and approximate otherwise.
+    /// Next to a step, the supremum of the function can be above the result, when the
+    /// function only tends to it before the excluded higher bound of a segment.
+    /// Undefined parts of the range are skipped, so check gaps if they matter.
+    /// Returns None if the function is undefined over the whole range.
+    pub fn max_output(&self, from: B, to: B, steps: usize) -> Option<O> {
+        self.extremum(from, to, steps, Ordering::Greater)
+    }
+
+    /// Returns the lowest output found by sampling the function over [from, to].
+    /// Sampling works like max_output.
+    pub fn min_output(&self, from: B, to: B, steps: usize) -> Option<O> {
+        self.extremum(from, to, steps, Ordering::Less)
+    }
+
+    /// Samples the function over [from, to], keeping the output which compares to the others
+    /// as wanted.
+    fn extremum(&self, from: B, to: B, steps: usize, wanted: Ordering) -> Option<O> {
+        let (from, to) = if to < from { (to, from) } else { (from, to) };
+        if from == to {
+            return self.eval(from);
+        }
+        let steps = steps.max(1);
+        let mut best: Option<O> = None;
+        for (lower, higher, f) in self.pieces(from.to_f64(), to.to_f64()) {
+            let f = match f {
+                Some(f) => f,
+                None => continue,
+            };
+            let width = (higher - lower) / steps as f64;
+            for k in 0..=steps {
+                let y = if k == 0 || k == steps {
+                    // The bounds of the piece may be excluded from its segment.
+                    let x = if k == 0 { lower } else { higher };
+                    match self.eval(B::from_f64(x)) {
+                        Some(y) => y,
+                        None => continue,
+                    }
+                } else {
+                    f(B::from_f64(lower + width * k as f64))
+                };
+                if best
+                    .as_ref()
+                    .is_none_or(|b| y.partial_cmp(b) == Some(wanted))
+                {
+                    best = Some(y);
+                }
+            }
+        }
+        best
+    }
+}
+
+impl<'a> PartialFunction<'a, f64, f64> {
+    /// Averages the function over [x - w, x + w], sampled at `samples` evenly spaced values,
+    /// bounds included.
+    ///
+    /// Only the defined samples are averaged, so a window partially outside the domain or
+    /// over a gap still gives a value.
+    /// Returns None if no sample is defined.
+    pub fn window_average(&self, x: f64, w: f64, samples: usize) -> Option<f64> {
+        let (sum, count) = (0..samples)
+            .filter_map(|i| {
+                let t = if samples > 1 {
+                    -w + 2.0 * w * i as f64 / (samples - 1) as f64
+                } else {
+                    0.0
+                };
+                self.eval(x + t)
+            })
+            .fold((0.0, 0), |(sum, count), y| (sum + y, count + 1));
+        if count == 0 {
+            None
+        } else {
+            Some(sum / count as f64)
+        }
+    }
+
+    /// Compares two functions by sampling them at `samples` evenly spaced points
+    /// covering the union of their domains, bounds included.
+    ///
+    /// Returns true if both are undefined at every sample or both are defined and
+    /// within epsilon of each other. Two empty functions are equal.
+    pub fn approx_eq(
+        &self,
+        other: &PartialFunction<f64, f64>,
+        samples: usize,
+        epsilon: f64,
+    ) -> bool {
+        let (lower, higher) = match (self.domain(), other.domain()) {
+            (Some(a), Some(b)) => (a.0.min(b.0), a.1.max(b.1)),
+            (Some(d), None) | (None, Some(d)) => d,
+            (None, None) => return true,
+        };
+        (0..samples).all(|i| {
+            let x = if samples > 1 {
+                lower + (higher - lower) * i as f64 / (samples - 1) as f64
+            } else {
+                lower
+            };
+            match (self.eval(x), other.eval(x)) {
+                (Some(a), Some(b)) => (a - b).abs() <= epsilon,
+                (None, None) => true,
+                _ => false,
+            }
+        })
+    }
+}
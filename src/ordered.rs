@@ -0,0 +1,112 @@
+use crate::in_segment;
+use std::collections::BTreeMap;
+use std::ops::Bound;
+
+/// Segments keyed by lower bound, storing the higher bound and the function.
+type Segments<'a, B, O> = BTreeMap<B, (B, Box<dyn Fn(B) -> O + 'a>)>;
+
+/// A partial function for totally ordered bounds, storing its segments in a BTreeMap
+/// keyed by lower bound.
+///
+/// Follows the same rules as PartialFunction: segments are [lower,higher[, except for the
+/// last one which includes its higher bound, and points can be placed on the excluded
+/// higher bound of a segment.
+/// Finding the segment of a value and inserting or removing a segment are O(log n),
+/// which makes it a better fit than PartialFunction for functions modified after being built.
+pub struct OrderedPartialFunction<'a, B: Ord, O> {
+    segments: Segments<'a, B, O>,
+}
+
+/// Checks if the segment [lower,higher] can be added to segments without overlapping another.
+fn fits<B: Ord, O>(segments: &Segments<'_, B, O>, lower: &B, higher: &B) -> bool {
+    if lower > higher {
+        return false;
+    }
+    let prev_ok = segments
+        .range(..=lower)
+        .next_back()
+        .is_none_or(|(prev_lower, (prev_higher, _))| prev_lower != lower && lower >= prev_higher);
+    let next_ok = segments
+        .range((Bound::Excluded(lower), Bound::Unbounded))
+        .next()
+        .is_none_or(|(next_lower, _)| next_lower >= higher);
+    prev_ok && next_ok
+}
+
+impl<'a, B: Ord, O> OrderedPartialFunction<'a, B, O> {
+    /// Creates a new OrderedPartialFunctionBuilder.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> OrderedPartialFunctionBuilder<'a, B, O> {
+        OrderedPartialFunctionBuilder::new()
+    }
+
+    /// Evaluates the partial function.
+    /// Returns None if no function is defined.
+    pub fn eval(&self, x: B) -> Option<O> {
+        let (lower, (higher, func)) = self.segments.range(..=&x).next_back()?;
+        let is_last = self.segments.keys().next_back() == Some(lower);
+        if in_segment(lower, higher, is_last, &x, &B::partial_cmp) {
+            Some(func(x))
+        } else {
+            None
+        }
+    }
+
+    /// Adds a segment to the built function.
+    /// Returns false, leaving the function unchanged, if the segment overlaps another one.
+    ///
+    /// Since only the last segment includes its higher bound, inserting a segment past the end
+    /// makes the higher bound of the previous last segment undefined, unless the new segment
+    /// starts there.
+    pub fn insert(&mut self, lower: B, higher: B, func: Box<dyn Fn(B) -> O + 'a>) -> bool {
+        if !fits(&self.segments, &lower, &higher) {
+            return false;
+        }
+        self.segments.insert(lower, (higher, func));
+        true
+    }
+
+    /// Removes the segment starting at lower.
+    /// Returns false if no segment starts there.
+    pub fn remove(&mut self, lower: &B) -> bool {
+        self.segments.remove(lower).is_some()
+    }
+
+    /// Returns the number of segments.
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Checks if the function has no segment, in which case it is undefined everywhere.
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+}
+
+/// A builder to create an OrderedPartialFunction.
+#[derive(new)]
+pub struct OrderedPartialFunctionBuilder<'a, B: Ord, O> {
+    #[new(default)]
+    segments: Segments<'a, B, O>,
+}
+
+impl<'a, B: Ord, O> OrderedPartialFunctionBuilder<'a, B, O> {
+    /// Adds a bounded function bounded between [lower,higher[ of function func.
+    pub fn with(mut self, lower: B, higher: B, func: Box<dyn Fn(B) -> O + 'a>) -> Self {
+        debug_assert!(self.can_insert(&lower, &higher));
+        self.segments.insert(lower, (higher, func));
+        self
+    }
+
+    /// Check if you can safely insert into the function list for the specified bounds.
+    pub fn can_insert(&self, lower: &B, higher: &B) -> bool {
+        fits(&self.segments, lower, higher)
+    }
+
+    /// Builds the OrderedPartialFunction from the functions added using with.
+    pub fn build(self) -> OrderedPartialFunction<'a, B, O> {
+        OrderedPartialFunction {
+            segments: self.segments,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::{PartialFunction, Real};
+
+/// A partial function repeating itself every period.
+/// See PartialFunction::periodic.
+pub struct PeriodicPartialFunction<'a, B, O> {
+    func: PartialFunction<'a, B, O>,
+    start: B,
+    period: B,
+}
+
+impl<'a, B: Real, O> PartialFunction<'a, B, O> {
+    /// Repeats the function every period, starting from the lowest bound of its domain.
+    ///
+    /// Values are brought back into [lower, lower + period[ before being evaluated,
+    /// so gaps inside this interval stay undefined.
+    /// Panics if period is not strictly positive.
+    pub fn periodic(self, period: B) -> PeriodicPartialFunction<'a, B, O> {
+        assert!(
+            period > B::from_f64(0.0),
+            "the period of a function must be strictly positive"
+        );
+        let start = self
+            .breakpoints
+            .first()
+            .copied()
+            .unwrap_or_else(|| B::from_f64(0.0));
+        PeriodicPartialFunction {
+            func: self,
+            start,
+            period,
+        }
+    }
+
+    /// Repeats the function using the width of its domain as period.
+    /// Returns None if the function is empty, unbounded or has gaps.
+    pub fn periodic_domain(self) -> Option<PeriodicPartialFunction<'a, B, O>> {
+        let (lower, higher) = self.domain()?;
+        let unbounded = self.below.is_some() || self.above.is_some();
+        if unbounded || !self.gaps().is_empty() || higher <= lower {
+            return None;
+        }
+        Some(self.periodic(higher - lower))
+    }
+}
+
+impl<'a, B: Real, O> PeriodicPartialFunction<'a, B, O> {
+    /// Evaluates the function after bringing x back into the first period.
+    pub fn eval(&self, x: B) -> Option<O> {
+        let offset = x - self.start;
+        let wrapped = offset - (offset / self.period).floor() * self.period;
+        self.func.eval(self.start + wrapped)
+    }
+}
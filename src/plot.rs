@@ -0,0 +1,76 @@
+use crate::PartialFunction;
+
+/// Width of the column holding the output labels.
+const LABEL_WIDTH: usize = 10;
+
+impl<'a> PartialFunction<'a, f64, f64> {
+    /// Renders a text plot of the function sampled at width values evenly spread over [from, to].
+    ///
+    /// The plot is height rows tall, scaled to the lowest and highest defined outputs,
+    /// which are written on the first and last rows.
+    /// Undefined samples are left blank.
+    /// Returns an empty string if width or height is zero.
+    pub fn ascii_plot(&self, width: usize, height: usize, from: f64, to: f64) -> String {
+        if width == 0 || height == 0 {
+            return String::new();
+        }
+        let samples = (0..width)
+            .map(|i| {
+                let t = if width > 1 {
+                    i as f64 / (width - 1) as f64
+                } else {
+                    0.0
+                };
+                self.eval(from + (to - from) * t).filter(|y| y.is_finite())
+            })
+            .collect::<Vec<_>>();
+        let defined = samples.iter().flatten();
+        let min = defined.clone().copied().fold(f64::INFINITY, f64::min);
+        let max = defined.copied().fold(f64::NEG_INFINITY, f64::max);
+        // A flat function is drawn in the middle of the plot.
+        let (low, high) = if min < max {
+            (min, max)
+        } else {
+            (min - 1.0, max + 1.0)
+        };
+        let mut grid = vec![vec![' '; width]; height];
+        for (column, y) in samples.iter().enumerate() {
+            if let Some(y) = y {
+                let row = ((high - y) / (high - low) * (height - 1) as f64).round() as usize;
+                grid[row.min(height - 1)][column] = '*';
+            }
+        }
+        let mut plot = String::new();
+        for (row, cells) in grid.iter().enumerate() {
+            let label = if min > max {
+                String::new()
+            } else if row == 0 {
+                format!("{:.2}", high)
+            } else if row == height - 1 {
+                format!("{:.2}", low)
+            } else {
+                String::new()
+            };
+            plot.push_str(&format!("{:>w$} |", label, w = LABEL_WIDTH));
+            plot.extend(cells.iter());
+            plot.push('\n');
+        }
+        plot.push_str(&format!("{:>w$} +", "", w = LABEL_WIDTH));
+        plot.push_str(&"-".repeat(width));
+        plot.push('\n');
+        let from_label = format!("{:.2}", from);
+        let to_label = format!("{:.2}", to);
+        let padding = (width + 1)
+            .saturating_sub(from_label.len() + to_label.len())
+            .max(1);
+        plot.push_str(&format!(
+            "{:>w$} {}{}{}\n",
+            "",
+            from_label,
+            " ".repeat(padding),
+            to_label,
+            w = LABEL_WIDTH
+        ));
+        plot
+    }
+}
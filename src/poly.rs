@@ -0,0 +1,179 @@
+use crate::bounds::Bounds;
+use crate::{in_segment, is_undefined, reorder, BuildError, InsertError, OverlapError, Real};
+use std::convert::TryFrom;
+use std::fmt::Debug;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A polynomial defined between lower and higher.
+///
+/// Unlike a closure, it is plain data which can be cloned, compared, printed and serialized.
+/// The coefficients are by increasing degree, of the offset from lower: `coeffs[0]` is the
+/// value at lower and the polynomial is evaluated at x - lower.
+#[derive(new, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PolySegment<B> {
+    /// Coefficients by increasing degree.
+    pub coeffs: Vec<B>,
+    /// The lower bound of the segment.
+    pub lower: B,
+    /// The higher bound of the segment.
+    pub higher: B,
+}
+
+impl<B: Real> PolySegment<B> {
+    /// Creates a segment always returning value.
+    pub fn constant(lower: B, higher: B, value: B) -> Self {
+        PolySegment::new(vec![value], lower, higher)
+    }
+
+    /// Creates a segment going linearly from y0 at lower to y1 at higher.
+    pub fn linear(lower: B, higher: B, y0: B, y1: B) -> Self {
+        let slope = (y1 - y0) / (higher - lower);
+        PolySegment::new(vec![y0, slope], lower, higher)
+    }
+
+    /// Creates the cubic Hermite segment going from y0 with slope d0 at lower to y1 with
+    /// slope d1 at higher.
+    pub fn hermite(lower: B, higher: B, y0: B, y1: B, d0: B, d1: B) -> Self {
+        let width = higher - lower;
+        let secant = (y1 - y0) / width;
+        let two = B::from_f64(2.0);
+        let c2 = (B::from_f64(3.0) * secant - two * d0 - d1) / width;
+        let c3 = (d0 + d1 - two * secant) / (width * width);
+        PolySegment::new(vec![y0, d0, c2, c3], lower, higher)
+    }
+
+    /// Evaluates the polynomial at x using Horner's rule, ignoring the bounds.
+    pub fn eval(&self, x: B) -> B {
+        let t = x - self.lower;
+        self.coeffs
+            .iter()
+            .rev()
+            .fold(B::from_f64(0.0), |acc, &c| acc * t + c)
+    }
+}
+
+/// A partial function made of polynomial segments.
+///
+/// Segments follow the same rules as the ones of a PartialFunction, but being data, the whole
+/// function can be cloned, compared, printed and serialized.
+/// It is serialized as its list of segments, which is validated like the builder does when
+/// deserializing it.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        try_from = "Vec<PolySegment<B>>",
+        into = "Vec<PolySegment<B>>",
+        bound(
+            serialize = "B: Serialize + Clone",
+            deserialize = "B: Deserialize<'de> + Real + Debug"
+        )
+    )
+)]
+pub struct PolyPartialFunction<B> {
+    /// Segments sorted by lower bound.
+    segments: Vec<PolySegment<B>>,
+}
+
+impl<B: Real> PolyPartialFunction<B> {
+    /// Creates a new PolyPartialFunctionBuilder.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> PolyPartialFunctionBuilder<B> {
+        PolyPartialFunctionBuilder {
+            bounds: Bounds::new(),
+            segments: vec![],
+        }
+    }
+
+    /// Evaluates the partial function.
+    /// Returns None if no segment is defined.
+    pub fn eval(&self, x: B) -> Option<B> {
+        if is_undefined(&x) {
+            return None;
+        }
+        let i = self
+            .segments
+            .partition_point(|s| s.lower <= x)
+            .checked_sub(1)?;
+        let s = &self.segments[i];
+        let is_last = i + 1 == self.segments.len();
+        if in_segment(&s.lower, &s.higher, is_last, &x, &B::partial_cmp) {
+            Some(s.eval(x))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the segments, sorted by lower bound.
+    pub fn segments(&self) -> &[PolySegment<B>] {
+        &self.segments
+    }
+}
+
+impl<B: Real + Debug> TryFrom<Vec<PolySegment<B>>> for PolyPartialFunction<B> {
+    type Error = BuildError<B>;
+
+    /// Builds the function from segments in any order, returning every problem found instead
+    /// of panicking like the builder.
+    fn try_from(segments: Vec<PolySegment<B>>) -> Result<Self, Self::Error> {
+        let mut bounds = Bounds::new();
+        // Insertion index of each segment added to bounds, to report conflicts by index in
+        // segments.
+        let mut added = vec![];
+        let mut errors = vec![];
+        for (i, s) in segments.iter().enumerate() {
+            match bounds.try_push(s.lower, s.higher) {
+                Ok(()) => added.push(i),
+                Err(InsertError::Overlap(e)) => errors.push(InsertError::Overlap(OverlapError {
+                    first: added[e.first],
+                    second: i,
+                    at: e.at,
+                })),
+                Err(e) => errors.push(e),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(BuildError { errors });
+        }
+        Ok(PolyPartialFunctionBuilder { bounds, segments }.build())
+    }
+}
+
+impl<B> From<PolyPartialFunction<B>> for Vec<PolySegment<B>> {
+    fn from(func: PolyPartialFunction<B>) -> Self {
+        func.segments
+    }
+}
+
+/// A builder to create an immutable PolyPartialFunction.
+pub struct PolyPartialFunctionBuilder<B> {
+    /// Bounds of the segments in insertion order.
+    bounds: Bounds<B>,
+    /// Segments in insertion order.
+    segments: Vec<PolySegment<B>>,
+}
+
+impl<B: Real + Debug> PolyPartialFunctionBuilder<B> {
+    /// Adds a polynomial segment.
+    pub fn with(mut self, segment: PolySegment<B>) -> Self {
+        self.bounds.push(segment.lower, segment.higher);
+        self.segments.push(segment);
+        self
+    }
+
+    /// Check if you can safely insert into the function list for the specified bounds.
+    pub fn can_insert(&self, lower: &B, higher: &B) -> bool {
+        self.bounds.can_insert(lower, higher)
+    }
+
+    /// Builds the PolyPartialFunction from the segments added using with.
+    pub fn build(mut self) -> PolyPartialFunction<B> {
+        let order = self.bounds.sort();
+        let segments = reorder(self.segments, order.into_iter());
+        PolyPartialFunction { segments }
+    }
+}
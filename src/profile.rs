@@ -0,0 +1,39 @@
+use crate::{Part, PartialFunction};
+
+/// A partial function counting how many times each segment is evaluated.
+/// See PartialFunction::profiled.
+pub struct ProfiledPartialFunction<'a, B, O> {
+    func: PartialFunction<'a, B, O>,
+    hits: Vec<u64>,
+}
+
+impl<'a, B: PartialOrd, O> PartialFunction<'a, B, O> {
+    /// Counts the evaluations of each segment, to find the most used ones.
+    pub fn profiled(self) -> ProfiledPartialFunction<'a, B, O> {
+        let hits = vec![0; self.funcs.len()];
+        ProfiledPartialFunction { func: self, hits }
+    }
+}
+
+impl<'a, B: PartialOrd, O> ProfiledPartialFunction<'a, B, O> {
+    /// Evaluates the partial function, counting a hit for the segment used.
+    /// Evaluations of unbounded segments are not counted.
+    pub fn eval(&mut self, x: B) -> Option<O> {
+        let part = self.func.part_of(&x)?;
+        if let Part::Segment(i) = part {
+            self.hits[i] += 1;
+        }
+        let f = self.func.part_func(part);
+        Some(f(x))
+    }
+
+    /// Returns the number of evaluations of each segment, sorted by lower bound.
+    pub fn hit_counts(&self) -> &[u64] {
+        &self.hits
+    }
+
+    /// Returns the profiled function, dropping the counts.
+    pub fn into_inner(self) -> PartialFunction<'a, B, O> {
+        self.func
+    }
+}
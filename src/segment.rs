@@ -0,0 +1,110 @@
+use crate::bounds::Bounds;
+use crate::reorder;
+use std::fmt::Debug;
+use std::ops::{Add, Mul};
+
+/// The function of a segment, with variants for common cases that are evaluated
+/// without calling a boxed closure.
+pub enum SegmentFn<'a, B, O> {
+    /// Always returns the value.
+    Const(O),
+    /// Returns slope * x + intercept.
+    Linear {
+        /// Multiplies x.
+        slope: O,
+        /// Added after multiplying x by slope.
+        intercept: O,
+    },
+    /// Returns x.
+    Identity,
+    /// Calls the function.
+    Custom(Box<dyn Fn(B) -> O + 'a>),
+}
+
+impl<'a, B, O> SegmentFn<'a, B, O>
+where
+    B: Into<O>,
+    O: Clone + Add<Output = O> + Mul<Output = O>,
+{
+    /// Evaluates the function at x.
+    pub fn eval(&self, x: B) -> O {
+        match self {
+            SegmentFn::Const(value) => value.clone(),
+            SegmentFn::Linear { slope, intercept } => slope.clone() * x.into() + intercept.clone(),
+            SegmentFn::Identity => x.into(),
+            SegmentFn::Custom(func) => func(x),
+        }
+    }
+}
+
+/// A partial function whose segments hold a SegmentFn, avoiding a boxed closure for
+/// constant, linear and identity segments.
+///
+/// Segments follow the same rules as the ones of a PartialFunction.
+pub struct SegmentPartialFunction<'a, B, O> {
+    /// Bounds of the segments, sorted.
+    bounds: Bounds<B>,
+    /// Function of each segment, aligned with the bounds.
+    funcs: Vec<SegmentFn<'a, B, O>>,
+}
+
+impl<'a, B: PartialOrd, O> SegmentPartialFunction<'a, B, O> {
+    /// Creates a new SegmentPartialFunctionBuilder.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> SegmentPartialFunctionBuilder<'a, B, O> {
+        SegmentPartialFunctionBuilder {
+            bounds: Bounds::new(),
+            funcs: vec![],
+        }
+    }
+}
+
+impl<'a, B, O> SegmentPartialFunction<'a, B, O>
+where
+    B: PartialOrd + Into<O>,
+    O: Clone + Add<Output = O> + Mul<Output = O>,
+{
+    /// Evaluates the partial function.
+    /// Returns None if no function is defined.
+    pub fn eval(&self, x: B) -> Option<O> {
+        let i = self.bounds.index_of(&x)?;
+        Some(self.funcs[i].eval(x))
+    }
+}
+
+/// A builder to create an immutable SegmentPartialFunction.
+pub struct SegmentPartialFunctionBuilder<'a, B, O> {
+    /// Bounds of the segments in insertion order.
+    bounds: Bounds<B>,
+    /// Functions in insertion order.
+    funcs: Vec<SegmentFn<'a, B, O>>,
+}
+
+impl<'a, B: PartialOrd, O> SegmentPartialFunctionBuilder<'a, B, O> {
+    /// Adds a segment bounded between [lower,higher[ of function func.
+    pub fn with(mut self, lower: B, higher: B, func: SegmentFn<'a, B, O>) -> Self
+    where
+        B: Debug,
+    {
+        self.bounds.push(lower, higher);
+        self.funcs.push(func);
+        self
+    }
+
+    /// Check if you can safely insert into the function list for the specified bounds.
+    pub fn can_insert(&self, lower: &B, higher: &B) -> bool {
+        self.bounds.can_insert(lower, higher)
+    }
+}
+
+impl<'a, B: PartialOrd, O> SegmentPartialFunctionBuilder<'a, B, O> {
+    /// Builds the SegmentPartialFunction from the functions added using with.
+    pub fn build(mut self) -> SegmentPartialFunction<'a, B, O> {
+        let order = self.bounds.sort();
+        let funcs = reorder(self.funcs, order.into_iter());
+        SegmentPartialFunction {
+            bounds: self.bounds,
+            funcs,
+        }
+    }
+}
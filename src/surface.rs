@@ -0,0 +1,153 @@
+use crate::{
+    PartialConstFunction, PartialConstFunctionBuilder, PartialFunction, PartialFunctionBuilder,
+};
+
+/// A partial function of two variables, made of segments along x each holding a partial
+/// function of y.
+pub struct PartialFunction2D<'a> {
+    rows: PartialConstFunction<f64, PartialFunction<'a, f64, f64>>,
+}
+
+impl<'a> PartialFunction2D<'a> {
+    /// Creates a new PartialFunction2DBuilder.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> PartialFunction2DBuilder<'a> {
+        PartialFunction2DBuilder {
+            rows: PartialConstFunction::new(),
+        }
+    }
+
+    /// Evaluates the function of y of the segment containing x at y.
+    /// Returns None if no segment contains x or if its function is not defined at y.
+    pub fn eval2(&self, x: f64, y: f64) -> Option<f64> {
+        self.rows.eval_ref(&x)?.eval(y)
+    }
+}
+
+/// A builder to create an immutable PartialFunction2D.
+pub struct PartialFunction2DBuilder<'a> {
+    rows: PartialConstFunctionBuilder<f64, PartialFunction<'a, f64, f64>>,
+}
+
+impl<'a> PartialFunction2DBuilder<'a> {
+    /// Adds a segment bounded between [x_lower,x_higher[ along x, evaluated along y by the
+    /// function built from inner.
+    pub fn with(
+        self,
+        x_lower: f64,
+        x_higher: f64,
+        inner: PartialFunctionBuilder<'a, f64, f64>,
+    ) -> Self {
+        PartialFunction2DBuilder {
+            rows: self.rows.with(x_lower, x_higher, inner.build()),
+        }
+    }
+
+    /// Check if you can safely insert into the function list for the specified bounds along x.
+    pub fn can_insert(&self, x_lower: &f64, x_higher: &f64) -> bool {
+        self.rows.can_insert(x_lower, x_higher)
+    }
+
+    /// Builds the PartialFunction2D from the segments added using with.
+    pub fn build(self) -> PartialFunction2D<'a> {
+        PartialFunction2D {
+            rows: self.rows.build(),
+        }
+    }
+}
+
+/// A function of two variables defined on the rectangle [x_lower,x_higher[ x [y_lower,y_higher[.
+struct Rectangle<'a, B1, B2, O> {
+    func: Box<dyn Fn(B1, B2) -> O + 'a>,
+    x_lower: B1,
+    x_higher: B1,
+    y_lower: B2,
+    y_higher: B2,
+}
+
+impl<'a, B1: PartialOrd, B2: PartialOrd, O> Rectangle<'a, B1, B2, O> {
+    /// Checks if the rectangle contains (x, y).
+    fn contains(&self, x: &B1, y: &B2) -> bool {
+        &self.x_lower <= x && x < &self.x_higher && &self.y_lower <= y && y < &self.y_higher
+    }
+}
+
+/// A partial function of two variables made of non-overlapping rectangles, each with its own
+/// function.
+///
+/// Rectangles are [lower,higher[ on both axes, without any special case for the last one,
+/// so a rectangle never contains its higher bounds and touching rectangles don't overlap.
+pub struct PartialFunction2<'a, B1, B2, O> {
+    rects: Vec<Rectangle<'a, B1, B2, O>>,
+}
+
+impl<'a, B1: PartialOrd, B2: PartialOrd, O> PartialFunction2<'a, B1, B2, O> {
+    /// Creates a new PartialFunction2Builder.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> PartialFunction2Builder<'a, B1, B2, O> {
+        PartialFunction2Builder { rects: vec![] }
+    }
+
+    /// Evaluates the function of the rectangle containing (x, y).
+    /// Returns None if no rectangle contains it.
+    pub fn eval(&self, x: B1, y: B2) -> Option<O> {
+        let rect = self.rects.iter().find(|r| r.contains(&x, &y))?;
+        Some((rect.func)(x, y))
+    }
+
+    /// Returns the number of rectangles.
+    pub fn len(&self) -> usize {
+        self.rects.len()
+    }
+
+    /// Returns true if the function has no rectangles.
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+}
+
+/// A builder to create an immutable PartialFunction2.
+pub struct PartialFunction2Builder<'a, B1, B2, O> {
+    rects: Vec<Rectangle<'a, B1, B2, O>>,
+}
+
+impl<'a, B1: PartialOrd, B2: PartialOrd, O> PartialFunction2Builder<'a, B1, B2, O> {
+    /// Adds a function defined on [x_lower,x_higher[ x [y_lower,y_higher[.
+    pub fn with(
+        mut self,
+        x_lower: B1,
+        x_higher: B1,
+        y_lower: B2,
+        y_higher: B2,
+        func: Box<dyn Fn(B1, B2) -> O + 'a>,
+    ) -> Self {
+        debug_assert!(self.can_insert(&x_lower, &x_higher, &y_lower, &y_higher));
+        self.rects.push(Rectangle {
+            func,
+            x_lower,
+            x_higher,
+            y_lower,
+            y_higher,
+        });
+        self
+    }
+
+    /// Check if you can safely insert a rectangle with the specified bounds.
+    /// Bounds that can't be compared, empty rectangles and rectangles overlapping
+    /// another one are rejected.
+    pub fn can_insert(&self, x_lower: &B1, x_higher: &B1, y_lower: &B2, y_higher: &B2) -> bool {
+        x_lower < x_higher
+            && y_lower < y_higher
+            && !self.rects.iter().any(|r| {
+                x_lower < &r.x_higher
+                    && &r.x_lower < x_higher
+                    && y_lower < &r.y_higher
+                    && &r.y_lower < y_higher
+            })
+    }
+
+    /// Builds the PartialFunction2 from the rectangles added using with.
+    pub fn build(self) -> PartialFunction2<'a, B1, B2, O> {
+        PartialFunction2 { rects: self.rects }
+    }
+}
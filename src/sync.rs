@@ -0,0 +1,90 @@
+use crate::bounds::Bounds;
+use crate::reorder;
+use std::fmt::Debug;
+
+/// A segment function which can be shared between threads.
+type SyncFn<'a, B, O> = Box<dyn Fn(B) -> O + Send + Sync + 'a>;
+
+/// A partial function whose segment functions are Send and Sync, so the whole function
+/// can be shared between threads.
+///
+/// Segments follow the same rules as the ones of a PartialFunction.
+pub struct SyncPartialFunction<'a, B, O> {
+    /// Bounds of the segments, sorted.
+    bounds: Bounds<B>,
+    /// Function of each segment, aligned with the bounds.
+    funcs: Vec<SyncFn<'a, B, O>>,
+}
+
+impl<'a, B: PartialOrd, O> SyncPartialFunction<'a, B, O> {
+    /// Creates a new SyncPartialFunctionBuilder.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> SyncPartialFunctionBuilder<'a, B, O> {
+        SyncPartialFunctionBuilder {
+            bounds: Bounds::new(),
+            funcs: vec![],
+        }
+    }
+
+    /// Evaluates the partial function.
+    /// Returns None if no function is defined.
+    pub fn eval(&self, x: B) -> Option<O> {
+        let i = self.bounds.index_of(&x)?;
+        Some((self.funcs[i])(x))
+    }
+}
+
+impl<'a, B: PartialOrd + Clone, O> SyncPartialFunction<'a, B, O> {
+    /// Evaluates the partial function for each value of xs, in order.
+    pub fn eval_all(&self, xs: &[B]) -> Vec<Option<O>> {
+        xs.iter().map(|x| self.eval(x.clone())).collect()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'a, B: PartialOrd + Clone + Sync, O: Send> SyncPartialFunction<'a, B, O> {
+    /// Evaluates the partial function for each value of xs using all threads of the
+    /// rayon thread pool.
+    /// Results are in the same order as xs, like eval_all.
+    pub fn par_eval_all(&self, xs: &[B]) -> Vec<Option<O>> {
+        use rayon::prelude::*;
+        xs.par_iter().map(|x| self.eval(x.clone())).collect()
+    }
+}
+
+/// A builder to create an immutable SyncPartialFunction.
+pub struct SyncPartialFunctionBuilder<'a, B, O> {
+    /// Bounds of the segments in insertion order.
+    bounds: Bounds<B>,
+    /// Functions in insertion order.
+    funcs: Vec<SyncFn<'a, B, O>>,
+}
+
+impl<'a, B: PartialOrd, O> SyncPartialFunctionBuilder<'a, B, O> {
+    /// Adds a bounded function bounded between [lower,higher[ of function func.
+    pub fn with(mut self, lower: B, higher: B, func: SyncFn<'a, B, O>) -> Self
+    where
+        B: Debug,
+    {
+        self.bounds.push(lower, higher);
+        self.funcs.push(func);
+        self
+    }
+
+    /// Check if you can safely insert into the function list for the specified bounds.
+    pub fn can_insert(&self, lower: &B, higher: &B) -> bool {
+        self.bounds.can_insert(lower, higher)
+    }
+}
+
+impl<'a, B: PartialOrd, O> SyncPartialFunctionBuilder<'a, B, O> {
+    /// Builds the SyncPartialFunction from the functions added using with.
+    pub fn build(mut self) -> SyncPartialFunction<'a, B, O> {
+        let order = self.bounds.sort();
+        let funcs = reorder(self.funcs, order.into_iter());
+        SyncPartialFunction {
+            bounds: self.bounds,
+            funcs,
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use crate::{PartialFunction, PartialFunctionBuilder};
+
+/// A partial function with a fallback, making it defined everywhere.
+/// See PartialFunctionBuilder::build_total.
+pub struct TotalPartialFunction<'a, B, O> {
+    func: PartialFunction<'a, B, O>,
+    fallback: Box<dyn Fn(B) -> O + 'a>,
+}
+
+impl<'a, B: PartialOrd + Clone, O> PartialFunctionBuilder<'a, B, O> {
+    /// Builds a function using fallback wherever no segment is defined,
+    /// including at values that can't be compared like NaN floats.
+    pub fn build_total(self, fallback: Box<dyn Fn(B) -> O + 'a>) -> TotalPartialFunction<'a, B, O> {
+        TotalPartialFunction {
+            func: self.build(),
+            fallback,
+        }
+    }
+}
+
+impl<'a, B: PartialOrd, O> TotalPartialFunction<'a, B, O> {
+    /// Evaluates the function, using the fallback if no segment contains x.
+    pub fn eval(&self, x: B) -> O {
+        match self.func.func_at(&x) {
+            Some(f) => f(x),
+            None => (self.fallback)(x),
+        }
+    }
+
+    /// Returns the partial function, without the fallback.
+    pub fn partial(&self) -> &PartialFunction<'a, B, O> {
+        &self.func
+    }
+}
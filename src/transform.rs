@@ -0,0 +1,464 @@
+use crate::{
+    shared, DualBoundedFunction, LowerBoundedFunction, LowerPartialFunction, PartialFunction, Real,
+    ScaleError, Unbounded,
+};
+use std::cmp::Ordering;
+use std::ops::{Add, Sub};
+use std::rc::Rc;
+
+impl<'a, B, O> PartialFunction<'a, B, O>
+where
+    B: PartialOrd + Clone + Add<Output = B> + Sub<Output = B> + 'a,
+    O: 'a,
+{
+    /// Translates all the bounds by offset.
+    /// The resulting function evaluated at x + offset gives the same value as the original at x.
+    pub fn shift_domain(mut self, offset: B) -> PartialFunction<'a, B, O> {
+        // A uniform translation keeps the segments sorted.
+        let segments = self
+            .take_segments()
+            .into_iter()
+            .map(|b| {
+                let f = b.func;
+                let delta = offset.clone();
+                DualBoundedFunction {
+                    func: Box::new(move |x| f(x - delta.clone())),
+                    lower: b.lower + offset.clone(),
+                    higher: b.higher + offset.clone(),
+                }
+            })
+            .collect();
+        self.set_segments(segments);
+        let shift = |u: Unbounded<'a, B, O>| {
+            let f = u.func;
+            let delta = offset.clone();
+            Unbounded {
+                func: Box::new(move |x| f(x - delta.clone())),
+                bound: u.bound + offset.clone(),
+            }
+        };
+        self.below = self.below.take().map(shift);
+        self.above = self.above.take().map(shift);
+        self.rebuild_cache();
+        self
+    }
+}
+
+impl<'a, B: PartialOrd + Clone, O> PartialFunction<'a, B, O> {
+    /// Restricts the function to [lower, higher], making it undefined everywhere else.
+    ///
+    /// Segments outside of the window are dropped and segments crossing its bounds are trimmed.
+    /// As usual, the last remaining segment includes its higher bound, so when it gets trimmed
+    /// at the end of a gap the value at that bound becomes defined.
+    /// Unbounded segments become finite segments ending on the bounds of the window.
+    /// Bounds excluded by the function stay excluded while they are inside the window.
+    pub fn restrict(mut self, lower: B, higher: B) -> PartialFunction<'a, B, O> {
+        let layered = !self.precedence.is_empty();
+        let count = self.funcs.len();
+        let first_lower = self.lowers.first().cloned();
+        let (lowest_excluded, _) = self.excluded_ends();
+        let excluded_lowest = |b: &DualBoundedFunction<'a, B, O>, x: &B| {
+            lowest_excluded && !b.is_point() && Some(x) == first_lower.as_ref()
+        };
+        // The excluded highest bound stays excluded only if the window still reaches it.
+        let end_excluded = self.half_open
+            && self.above.is_none()
+            && self.breakpoints.last().is_some_and(|end| end <= &higher);
+        let (kept, mut funcs): (Vec<usize>, Vec<_>) = self
+            .take_segments()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, b)| {
+                let new_lower = if b.lower < lower {
+                    lower.clone()
+                } else {
+                    b.lower.clone()
+                };
+                let new_higher = if b.higher > higher {
+                    higher.clone()
+                } else {
+                    b.higher.clone()
+                };
+                // A single point is kept only if the segment defined it.
+                let keep = new_lower < new_higher
+                    || (new_lower == new_higher
+                        && new_lower >= b.lower
+                        && !excluded_lowest(&b, &new_lower)
+                        && (new_lower < b.higher
+                            || b.is_point()
+                            || (i + 1 == count && !self.half_open && new_lower == b.higher)));
+                if keep {
+                    let f = DualBoundedFunction {
+                        func: b.func,
+                        lower: new_lower,
+                        higher: new_higher,
+                    };
+                    Some((i, f))
+                } else {
+                    None
+                }
+            })
+            .unzip();
+        self.half_open = end_excluded;
+        if layered {
+            self.precedence = kept.into_iter().map(|i| self.precedence[i]).collect();
+        }
+        // Unbounded segments were evaluated first, so they keep precedence over the others.
+        if let Some(u) = self.above.take() {
+            if higher >= u.bound {
+                let start = if lower > u.bound {
+                    lower.clone()
+                } else {
+                    u.bound
+                };
+                funcs.push(DualBoundedFunction {
+                    func: u.func,
+                    lower: start,
+                    higher: higher.clone(),
+                });
+                if layered {
+                    self.precedence.push((i32::MAX, usize::MAX));
+                }
+            }
+        }
+        if let Some(u) = self.below.take() {
+            if lower < u.bound {
+                let ends_on_bound = higher >= u.bound;
+                // The bound itself was not defined by this segment.
+                if ends_on_bound && funcs.is_empty() {
+                    self.half_open = true;
+                }
+                let end = if ends_on_bound { u.bound } else { higher };
+                funcs.insert(
+                    0,
+                    DualBoundedFunction {
+                        func: u.func,
+                        lower,
+                        higher: end,
+                    },
+                );
+                if layered {
+                    self.precedence.insert(0, (i32::MAX, usize::MAX));
+                }
+            }
+        }
+        self.set_segments(funcs);
+        self.open_lowest = lowest_excluded && self.lowers.first() == first_lower.as_ref();
+        self.rebuild_cache();
+        self
+    }
+
+    /// Checks if the lowest lower bound and the highest higher bound of the segments are
+    /// excluded by the function, and not defined by any other part of it.
+    fn excluded_ends(&self) -> (bool, bool) {
+        let undefined = |x: &B| self.part_of(x).is_none();
+        let highest = self.highers.iter().fold(None, |a: Option<&B>, h| match a {
+            Some(a) if a >= h => Some(a),
+            _ => Some(h),
+        });
+        (
+            self.open_lowest && self.lowers.first().is_some_and(undefined),
+            self.half_open && highest.is_some_and(undefined),
+        )
+    }
+
+    /// Keeps only the segments whose (lower, higher) bounds satisfy pred, in place.
+    ///
+    /// The function then behaves as if the removed segments had never been inserted: the values
+    /// they defined become undefined unless another segment contains them, and the new last
+    /// segment includes its higher bound.
+    /// Unbounded segments are always kept.
+    pub fn retain_segments(&mut self, mut pred: impl FnMut(&B, &B) -> bool) {
+        let keep = self
+            .lowers
+            .iter()
+            .zip(&self.highers)
+            .map(|(lower, higher)| pred(lower, higher))
+            .collect::<Vec<_>>();
+        retain_aligned(&mut self.lowers, &keep);
+        retain_aligned(&mut self.highers, &keep);
+        retain_aligned(&mut self.funcs, &keep);
+        if !self.precedence.is_empty() {
+            retain_aligned(&mut self.precedence, &keep);
+        }
+        self.rebuild_cache();
+    }
+
+    /// Removes every segment ending at or before cutoff, except single points at cutoff.
+    pub fn drain_before(&mut self, cutoff: &B) {
+        self.retain_segments(|lower, higher| higher > cutoff || lower == cutoff);
+    }
+}
+
+/// Keeps the elements of v whose index is true in keep.
+fn retain_aligned<T>(v: &mut Vec<T>, keep: &[bool]) {
+    let mut keep = keep.iter();
+    v.retain(|_| *keep.next().expect("keep is aligned with v"));
+}
+
+impl<'a, B: PartialOrd + Clone + 'a, O: 'a> PartialFunction<'a, B, O> {
+    /// Splits the function in two at x: the first part is defined below x and the second from x.
+    ///
+    /// A segment containing x is cut in two segments sharing its function.
+    /// Since the first part never includes x, even its last segment is [lower,higher[.
+    /// Unbounded segments crossing x are cut the same way, the finite half becoming a segment.
+    pub fn split_at(mut self, x: B) -> (PartialFunction<'a, B, O>, PartialFunction<'a, B, O>) {
+        let layered = !self.precedence.is_empty();
+        let count = self.funcs.len();
+        let first_lower = self.lowers.first().cloned();
+        let (lowest_excluded, _) = self.excluded_ends();
+        let mut left = vec![];
+        let mut right = vec![];
+        for (i, b) in self.take_segments().into_iter().enumerate() {
+            let rank = if layered { self.precedence[i] } else { (0, 0) };
+            // Only the last segment includes its higher bound.
+            let ends_on_x = b.higher == x && i + 1 == count && !self.half_open;
+            if b.lower >= x {
+                right.push((rank, b));
+            } else if b.higher <= x && !ends_on_x {
+                left.push((rank, b));
+            } else {
+                let f = Rc::new(b.func);
+                let lower = DualBoundedFunction {
+                    func: shared(&f),
+                    lower: b.lower,
+                    higher: x.clone(),
+                };
+                let higher = DualBoundedFunction {
+                    func: shared(&f),
+                    lower: x.clone(),
+                    higher: b.higher,
+                };
+                left.push((rank, lower));
+                right.push((rank, higher));
+            }
+        }
+        let mut below = None;
+        let mut above = None;
+        let mut right_half_open = self.half_open;
+        // Unbounded segments were evaluated first, so they keep precedence over the others.
+        if let Some(u) = self.below.take() {
+            if u.bound <= x {
+                below = Some(u);
+            } else {
+                let f = Rc::new(u.func);
+                below = Some(Unbounded {
+                    func: shared(&f),
+                    bound: x.clone(),
+                });
+                // The bound itself was not defined by this segment.
+                right_half_open |= right.is_empty();
+                let higher = DualBoundedFunction {
+                    func: shared(&f),
+                    lower: x.clone(),
+                    higher: u.bound,
+                };
+                right.insert(0, ((i32::MAX, usize::MAX), higher));
+            }
+        }
+        if let Some(u) = self.above.take() {
+            if u.bound >= x {
+                above = Some(u);
+            } else {
+                let f = Rc::new(u.func);
+                let lower = DualBoundedFunction {
+                    func: shared(&f),
+                    lower: u.bound,
+                    higher: x.clone(),
+                };
+                left.push(((i32::MAX, usize::MAX), lower));
+                above = Some(Unbounded {
+                    func: shared(&f),
+                    bound: x,
+                });
+            }
+        }
+        let part = |segments: Vec<((i32, usize), DualBoundedFunction<'a, B, O>)>,
+                    half_open: bool| {
+            let (precedence, funcs): (Vec<_>, Vec<_>) = segments.into_iter().unzip();
+            let mut func = PartialFunction::from_funcs(funcs);
+            if layered {
+                func.precedence = precedence;
+            }
+            func.half_open = half_open;
+            func.open_lowest = lowest_excluded && func.lowers.first() == first_lower.as_ref();
+            func
+        };
+        let mut left = part(left, true);
+        let mut right = part(right, right_half_open);
+        left.below = below;
+        right.above = above;
+        left.rebuild_cache();
+        right.rebuild_cache();
+        (left, right)
+    }
+}
+
+impl<'a, O: 'a> PartialFunction<'a, f64, O> {
+    /// Translates the function along x by delta.
+    /// The resulting function evaluated at x + delta gives the same value as the original at x.
+    pub fn shift(self, delta: f64) -> PartialFunction<'a, f64, O> {
+        self.shift_domain(delta)
+    }
+
+    /// Mirrors the function about the vertical line at axis.
+    /// The resulting function evaluated at 2 * axis - x gives the same value as the original at x.
+    ///
+    /// As with scale_domain by a negative factor, the order of the segments is reversed,
+    /// a value shared by two touching segments is evaluated by the segment that was the lower
+    /// one before mirroring and unbounded segments swap sides, as do excluded bounds.
+    pub fn mirror(self, axis: f64) -> PartialFunction<'a, f64, O> {
+        self.scale_domain(-1.0)
+            .expect("-1 is a valid factor")
+            .shift_domain(2.0 * axis)
+    }
+}
+
+impl<'a, B: Real + 'a, O: 'a> PartialFunction<'a, B, O> {
+    /// Stretches the function along x by factor.
+    /// The resulting function evaluated at x * factor gives the same value as the original at x.
+    ///
+    /// A negative factor mirrors the function, which reverses the order of the segments.
+    /// In that case, a value shared by two touching segments is evaluated by the segment that
+    /// was the lower one before scaling, and unbounded segments swap sides.
+    /// Excluded bounds also swap sides, so mirroring a function built with build_strict gives
+    /// a function defined at its highest bound but not at its lowest.
+    /// Returns an error if factor is zero or can't be compared, like NaN.
+    pub fn scale_domain(mut self, factor: B) -> Result<PartialFunction<'a, B, O>, ScaleError<B>> {
+        let zero = B::from_f64(0.0);
+        if !matches!(
+            factor.partial_cmp(&zero),
+            Some(Ordering::Greater) | Some(Ordering::Less)
+        ) {
+            return Err(ScaleError { factor });
+        }
+        let (lowest_excluded, highest_excluded) = self.excluded_ends();
+        let mut segments = self
+            .take_segments()
+            .into_iter()
+            .map(|b| {
+                let f = b.func;
+                let (lower, higher) = if factor > zero {
+                    (b.lower * factor, b.higher * factor)
+                } else {
+                    (b.higher * factor, b.lower * factor)
+                };
+                DualBoundedFunction {
+                    func: Box::new(move |x| f(x / factor)),
+                    lower,
+                    higher,
+                }
+            })
+            .collect::<Vec<_>>();
+        let scale = |u: Unbounded<'a, B, O>| {
+            let f = u.func;
+            Unbounded {
+                func: Box::new(move |x| f(x / factor)),
+                bound: u.bound * factor,
+            }
+        };
+        self.below = self.below.take().map(scale);
+        self.above = self.above.take().map(scale);
+        if factor < zero {
+            // Sort the mirrored segments again, keeping their precedence aligned.
+            let mut ranked = segments
+                .into_iter()
+                .zip(
+                    self.precedence
+                        .drain(..)
+                        .map(Some)
+                        .chain(std::iter::repeat(None)),
+                )
+                .collect::<Vec<_>>();
+            ranked.sort_by(|(a, _), (b, _)| {
+                a.lower
+                    .partial_cmp(&b.lower)
+                    .unwrap_or(Ordering::Equal)
+                    .then_with(|| a.higher.partial_cmp(&b.higher).unwrap_or(Ordering::Equal))
+            });
+            let (sorted, precedence): (Vec<_>, Vec<_>) = ranked.into_iter().unzip();
+            segments = sorted;
+            self.precedence = precedence.into_iter().flatten().collect();
+            std::mem::swap(&mut self.below, &mut self.above);
+            // The excluded ends swap sides too.
+            self.open_lowest = highest_excluded;
+            self.half_open = lowest_excluded;
+        }
+        self.set_segments(segments);
+        self.rebuild_cache();
+        Ok(self)
+    }
+}
+
+impl<'a, B, O, L> LowerPartialFunction<'a, B, O, L>
+where
+    B: PartialOrd + Clone + Add<Output = B> + Sub<Output = B> + 'a,
+    O: 'a,
+{
+    /// Translates all the lower bounds by offset.
+    /// The resulting function evaluated at x + offset gives the same value as the original at x.
+    pub fn shift_domain(self, offset: B) -> LowerPartialFunction<'a, B, O, L> {
+        let funcs = self
+            .funcs
+            .into_iter()
+            .map(|b| {
+                let f = b.func;
+                let delta = offset.clone();
+                LowerBoundedFunction {
+                    func: Box::new(move |x| f(x - delta.clone())),
+                    lower: b.lower + offset.clone(),
+                }
+            })
+            .collect();
+        LowerPartialFunction {
+            funcs,
+            labels: self.labels,
+        }
+    }
+}
+
+impl<'a, B: Real + 'a, O: 'a, L> LowerPartialFunction<'a, B, O, L> {
+    /// Stretches the function along x by factor.
+    /// The resulting function evaluated at x * factor gives the same value as the original at x.
+    ///
+    /// Returns an error if factor is not strictly positive, as lower bounds can't be mirrored.
+    pub fn scale_domain(
+        self,
+        factor: B,
+    ) -> Result<LowerPartialFunction<'a, B, O, L>, ScaleError<B>> {
+        if factor.partial_cmp(&B::from_f64(0.0)) != Some(Ordering::Greater) {
+            return Err(ScaleError { factor });
+        }
+        let funcs = self
+            .funcs
+            .into_iter()
+            .map(|b| {
+                let f = b.func;
+                LowerBoundedFunction {
+                    func: Box::new(move |x| f(x / factor)),
+                    lower: b.lower * factor,
+                }
+            })
+            .collect();
+        Ok(LowerPartialFunction {
+            funcs,
+            labels: self.labels,
+        })
+    }
+}
+
+impl<'a, B: PartialOrd, O, L> LowerPartialFunction<'a, B, O, L> {
+    /// Keeps only the segments whose lower bound satisfies pred, in place.
+    ///
+    /// The function then behaves as if the removed segments had never been inserted, so the
+    /// segment before a removed one extends up to the next remaining lower bound.
+    pub fn retain_segments(&mut self, mut pred: impl FnMut(&B) -> bool) {
+        let keep = self
+            .funcs
+            .iter()
+            .map(|b| pred(&b.lower))
+            .collect::<Vec<_>>();
+        retain_aligned(&mut self.funcs, &keep);
+        retain_aligned(&mut self.labels, &keep);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::PartialFunction;
+use std::rc::Rc;
+
+/// Two partial functions evaluated together, defined only where both are.
+/// See PartialFunction::zip.
+pub struct ZippedPartialFunction<'a, B, O> {
+    func: Box<dyn Fn(B) -> Option<O> + 'a>,
+}
+
+impl<'a, B: PartialOrd + Clone + 'a, O: 'a> PartialFunction<'a, B, O> {
+    /// Combines two functions over the same domain into one returning both of their outputs.
+    pub fn zip<O2: 'a>(
+        self,
+        other: PartialFunction<'a, B, O2>,
+    ) -> ZippedPartialFunction<'a, B, (O, O2)> {
+        self.zip_with(other, |a, b| (a, b))
+    }
+
+    /// Combines two functions over the same domain into one merging their outputs with f.
+    pub fn zip_with<O2: 'a, O3>(
+        self,
+        other: PartialFunction<'a, B, O2>,
+        f: impl Fn(O, O2) -> O3 + 'a,
+    ) -> ZippedPartialFunction<'a, B, O3> {
+        ZippedPartialFunction {
+            func: Box::new(move |x: B| {
+                let a = self.eval(x.clone())?;
+                let b = other.eval(x)?;
+                Some(f(a, b))
+            }),
+        }
+    }
+}
+
+/// The functions used by two partial functions at the same value.
+type FnPair<'s> = (&'s dyn Fn(f64) -> f64, &'s dyn Fn(f64) -> f64);
+
+impl<'a> PartialFunction<'a, f64, f64> {
+    /// Creates the function merging the outputs of both functions with op, defined only where
+    /// both are.
+    ///
+    /// Unlike zip_with, the result is a PartialFunction with one segment per sub-interval
+    /// between the bounds of both functions where both are defined, which borrows the
+    /// functions of both instead of consuming them.
+    pub fn combine<'s>(
+        &'s self,
+        other: &'s PartialFunction<f64, f64>,
+        op: impl Fn(f64, f64) -> f64 + 's,
+    ) -> PartialFunction<'s, f64, f64> {
+        let mut cuts = self
+            .breakpoints()
+            .iter()
+            .chain(other.breakpoints())
+            .copied()
+            .collect::<Vec<_>>();
+        cuts.sort_by(|a, b| a.partial_cmp(b).expect("bounds are never NaN"));
+        cuts.dedup();
+        let (first, last) = match (cuts.first(), cuts.last()) {
+            (Some(&first), Some(&last)) => (first, last),
+            _ => return PartialFunction::new().build(),
+        };
+        let both = |x: f64| -> Option<FnPair<'s>> { Some((self.func_at(&x)?, other.func_at(&x)?)) };
+        let op = Rc::new(op);
+        let combined = |(f, g): FnPair<'s>| -> Box<dyn Fn(f64) -> f64 + 's> {
+            let op = Rc::clone(&op);
+            Box::new(move |x| op(f(x), g(x)))
+        };
+        let below = both(f64::NEG_INFINITY);
+        let above = both(f64::INFINITY);
+        let pieces = cuts
+            .windows(2)
+            .filter_map(|w| Some((w[0], w[1], both(w[0] + (w[1] - w[0]) * 0.5)?)))
+            .collect::<Vec<_>>();
+        // Values where both are defined but no sub-interval starts, which need a point segment
+        // unless they are the included highest bound of the result.
+        let points = cuts
+            .iter()
+            .filter(|&&c| {
+                let starts = pieces.iter().any(|p| p.0 == c) || (above.is_some() && c == last);
+                !starts
+            })
+            .filter_map(|&c| Some((c, both(c)?)))
+            .collect::<Vec<_>>();
+        let first_piece_start = pieces.first().map(|p| p.0);
+        let last_piece_end = pieces.last().map(|p| p.1);
+        let first_point = points.first().map(|p| p.0);
+        let last_point = points.last().map(|p| p.0);
+        // The outer bounds of the pieces are excluded where either function excludes them.
+        let open_lowest = below.is_none()
+            && first_piece_start
+                .is_some_and(|s| both(s).is_none() && first_point.is_none_or(|p| p > s));
+        let open_highest = above.is_none()
+            && last_piece_end
+                .is_some_and(|e| both(e).is_none() && last_point.is_none_or(|p| p < e));
+        let mut result = PartialFunction::new();
+        if let Some(fg) = below {
+            result = result.with_unbounded_lower(first, combined(fg));
+        }
+        for (lower, higher, fg) in pieces {
+            result = result.with(lower, higher, combined(fg));
+        }
+        for (x, fg) in points {
+            let included = above.is_none() && Some(x) == last_piece_end && Some(x) == last_point;
+            if !included {
+                result = result.with_point(x, combined(fg));
+            }
+        }
+        if let Some(fg) = above {
+            result = result.with_unbounded_upper(last, combined(fg));
+        }
+        let mut result = if open_highest {
+            result.build_strict()
+        } else {
+            result.build()
+        };
+        result.open_lowest = open_lowest;
+        result
+    }
+}
+
+impl<'a, B, O> ZippedPartialFunction<'a, B, O> {
+    /// Evaluates both functions and combines their outputs.
+    /// Returns None if either function is not defined at x.
+    pub fn eval(&self, x: B) -> Option<O> {
+        (self.func)(x)
+    }
+}
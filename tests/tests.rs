@@ -123,4 +123,2253 @@ mod tests {
             .with(0.0, Box::new(|x| 2))
             .build();
     }
+
+    /// Sorted pseudo random values in [-1, 5[ along with the segment boundaries.
+    fn sorted_inputs() -> Vec<f64> {
+        let mut seed = 12345u64;
+        let mut xs = vec![0.0, 1.0, 2.5, 4.0];
+        for _ in 0..500 {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            xs.push((seed >> 11) as f64 / (1u64 << 53) as f64 * 6.0 - 1.0);
+        }
+        xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        xs
+    }
+
+    #[test]
+    fn eval_sorted_matches_eval() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(2.5, 4.0, Box::new(|x| x * 3.0))
+            .with(1.0, 2.5, Box::new(|x| x * 2.0))
+            .build();
+        let xs = sorted_inputs();
+        let expected = xs.iter().map(|x| p.eval(*x)).collect::<Vec<_>>();
+        assert_eq!(expected, p.eval_sorted(&xs));
+    }
+
+    #[test]
+    fn lower_eval_sorted_matches_eval() {
+        let f = LowerPartialFunction::new()
+            .with(1.0, Box::new(|x| x * 2.0))
+            .with(0.0, Box::new(|x| x))
+            .with(2.5, Box::new(|x| x * 3.0))
+            .build();
+        let xs = sorted_inputs();
+        let expected = xs.iter().map(|x| f.eval(*x)).collect::<Vec<_>>();
+        assert_eq!(expected, f.eval_sorted(&xs));
+    }
+
+    #[test]
+    fn structure_eq() {
+        let a = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| 5.0))
+            .build();
+        let b = PartialFunction::new()
+            .with(1.0, 2.0, Box::new(|x| x * 2.0))
+            .with(0.0, 1.0, Box::new(|x| 3.0))
+            .build();
+        let c = PartialFunction::new()
+            .with(0.0, 1.5, Box::new(|x| x))
+            .with(1.5, 2.0, Box::new(|x| 5.0))
+            .build();
+        assert!(a.structure_eq(&b));
+        assert!(!a.structure_eq(&c));
+    }
+
+    #[test]
+    fn eval_stable() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| 5.0))
+            .build();
+        assert_eq!(Some((5.0, false)), p.eval_stable(1.05, 0.1));
+        assert_eq!(Some((0.5, true)), p.eval_stable(0.5, 0.1));
+        assert_eq!(None, p.eval_stable(3.0, 0.1));
+    }
+
+    #[test]
+    fn shift() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| x * 2.0))
+            .build()
+            .shift(10.0);
+        assert_eq!(None, p.eval(0.5));
+        assert_eq!(Some(0.5), p.eval(10.5));
+        assert_eq!(Some(2.0), p.eval(11.0));
+        assert_eq!(Some(4.0), p.eval(12.0));
+    }
+
+    #[test]
+    fn breakpoints_cache() {
+        let p = PartialFunction::new()
+            .with(2.0, 3.0, Box::new(|x| x))
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| x))
+            .with(5.0, 6.0, Box::new(|x| x))
+            .build();
+        assert_eq!(&[0.0, 1.0, 2.0, 3.0, 5.0, 6.0], p.breakpoints());
+        let shifted = p.shift(1.0);
+        assert_eq!(&[1.0, 2.0, 3.0, 4.0, 6.0, 7.0], shifted.breakpoints());
+        assert_eq!(Some((1.0, 7.0)), shifted.domain());
+        assert_eq!(Some((1.0, 7.0)), shifted.domain());
+        assert_eq!(vec![(4.0, 6.0)], shifted.gaps());
+    }
+
+    #[test]
+    fn empty_domain() {
+        let p = PartialFunction::<f64, f64>::new().build();
+        assert!(p.breakpoints().is_empty());
+        assert_eq!(None, p.domain());
+    }
+
+    #[test]
+    fn shift_domain() {
+        let p = PartialFunction::new()
+            .with(0, 10, Box::new(|x| x * 2))
+            .build()
+            .shift_domain(5);
+        assert_eq!(None, p.eval(4));
+        assert_eq!(Some(0), p.eval(5));
+        assert_eq!(Some(20), p.eval(15));
+    }
+
+    #[test]
+    fn lower_shift_domain() {
+        let f = LowerPartialFunction::new()
+            .with(0.0, Box::new(|x| x))
+            .with(1.0, Box::new(|x| x * 2.0))
+            .build()
+            .shift_domain(-1.0);
+        assert_eq!(f.eval(-1.5), None);
+        assert_eq!(f.eval(-0.5), Some(0.5));
+        assert_eq!(f.eval(1.0), Some(4.0));
+    }
+
+    #[test]
+    fn scale_domain() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| 5.0))
+            .build()
+            .scale_domain(10.0)
+            .unwrap();
+        assert_eq!(Some(0.5), p.eval(5.0));
+        assert_eq!(Some(5.0), p.eval(10.0));
+        assert_eq!(Some(5.0), p.eval(20.0));
+        assert_eq!(None, p.eval(21.0));
+    }
+
+    #[test]
+    fn scale_domain_negative() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| 5.0))
+            .build()
+            .scale_domain(-2.0)
+            .unwrap();
+        assert_eq!(&[-4.0, -2.0, 0.0], p.breakpoints());
+        assert_eq!(Some(5.0), p.eval(-3.0));
+        assert_eq!(Some(1.0), p.eval(-2.0));
+        assert_eq!(Some(0.5), p.eval(-1.0));
+        assert_eq!(Some(0.0), p.eval(0.0));
+        assert_eq!(None, p.eval(1.0));
+    }
+
+    #[test]
+    fn scale_domain_invalid() {
+        let f = || {
+            PartialFunction::new()
+                .with(0.0, 1.0, Box::new(|x| x))
+                .build()
+        };
+        assert_eq!(
+            Some(ScaleError { factor: 0.0 }),
+            f().scale_domain(0.0).err()
+        );
+        assert!(f().scale_domain(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn scale_domain_negative_layered() {
+        let p = PartialFunction::new()
+            .with(0.0, 10.0, Box::new(|x| 0.0))
+            .with_priority(2.0, 3.0, 1, Box::new(|x| 1.0))
+            .build()
+            .scale_domain(-1.0)
+            .unwrap();
+        assert_eq!(Some((&-10.0, &0.0)), p.first_segment());
+        assert_eq!(Some((&-3.0, &-2.0)), p.last_segment());
+        assert_eq!(Some(1.0), p.eval(-2.5));
+        assert_eq!(Some(0.0), p.eval(-5.0));
+        assert_eq!(Some(0.0), p.eval(-10.0));
+        assert_eq!(Some(0.0), p.eval(0.0));
+    }
+
+    #[test]
+    fn lower_scale_domain() {
+        let f = LowerPartialFunction::new()
+            .with(0.0f32, Box::new(|x| x))
+            .with(1.0, Box::new(|x| 5.0))
+            .build()
+            .scale_domain(0.5)
+            .unwrap();
+        assert_eq!(f.eval(-0.1), None);
+        assert_eq!(f.eval(0.25), Some(0.5));
+        assert_eq!(f.eval(0.5), Some(5.0));
+    }
+
+    #[test]
+    fn lower_scale_domain_negative() {
+        let f = || {
+            LowerPartialFunction::new()
+                .with(0.0, Box::new(|x| x))
+                .build()
+        };
+        assert_eq!(
+            Some(ScaleError { factor: -1.0 }),
+            f().scale_domain(-1.0).err()
+        );
+        assert!(f().scale_domain(0.0).is_err());
+        assert!(f().scale_domain(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn walk() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x * 2.0))
+            .build();
+        let values = p.walk(0.0, 0.5, 3).collect::<Vec<_>>();
+        assert_eq!(
+            vec![(0.0, Some(0.0)), (0.5, Some(1.0)), (1.0, Some(2.0))],
+            values
+        );
+        let values = p.walk(1.0, -0.75, 3).collect::<Vec<_>>();
+        assert_eq!(
+            vec![(1.0, Some(2.0)), (0.25, Some(0.5)), (-0.5, None)],
+            values
+        );
+    }
+
+    #[test]
+    fn restrict() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| 5.0))
+            .with(2.0, 3.0, Box::new(|x| 6.0))
+            .build()
+            .restrict(0.5, 1.5);
+        assert_eq!(None, p.eval(0.4));
+        assert_eq!(Some(0.5), p.eval(0.5));
+        assert_eq!(Some(5.0), p.eval(1.0));
+        assert_eq!(Some(5.0), p.eval(1.5));
+        assert_eq!(None, p.eval(2.5));
+    }
+
+    #[test]
+    fn restrict_on_boundary() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| 5.0))
+            .build()
+            .restrict(0.0, 1.0);
+        assert_eq!(Some(0.5), p.eval(0.5));
+        assert_eq!(Some(5.0), p.eval(1.0));
+        assert_eq!(None, p.eval(1.5));
+        assert_eq!(&[0.0, 1.0], p.breakpoints());
+    }
+
+    #[test]
+    fn preserve_order() {
+        let p = PartialFunction::new()
+            .with(1.0, 2.0, Box::new(|x| 5.0))
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(3.0, 4.0, Box::new(|x| 6.0))
+            .build_preserve_order();
+        assert_eq!(Some(0.5), p.eval(0.5));
+        assert_eq!(Some(5.0), p.eval(1.0));
+        assert_eq!(None, p.eval(2.0));
+        assert_eq!(None, p.eval(2.5));
+        assert_eq!(Some(6.0), p.eval(4.0));
+        assert_eq!(None, p.eval(4.5));
+    }
+
+    #[test]
+    fn priority() {
+        let p = PartialFunction::new()
+            .with(0.0, 10.0, Box::new(|x| 0.0))
+            .with_priority(2.0, 4.0, 1, Box::new(|x| 1.0))
+            .with_priority(3.0, 5.0, 2, Box::new(|x| 2.0))
+            .with_priority(6.0, 7.0, -1, Box::new(|x| -1.0))
+            .build();
+        assert_eq!(Some(0.0), p.eval(1.0));
+        assert_eq!(Some(1.0), p.eval(2.5));
+        assert_eq!(Some(2.0), p.eval(3.5));
+        assert_eq!(Some(2.0), p.eval(4.5));
+        assert_eq!(Some(0.0), p.eval(5.0));
+        assert_eq!(Some(0.0), p.eval(6.5));
+        assert_eq!(None, p.eval(11.0));
+    }
+
+    #[test]
+    fn priority_overlap() {
+        let builder = PartialFunction::new()
+            .with(0.0, 10.0, Box::new(|x: f64| 0.0))
+            .with_priority(2.0, 4.0, 1, Box::new(|x| 1.0));
+        assert!(!builder.can_insert(&1.0, &2.0));
+        assert!(builder.can_insert_priority(&1.0, &2.0, 1));
+        assert!(!builder.can_insert_priority(&3.0, &5.0, 1));
+        assert_eq!(Ok(()), builder.validate());
+    }
+
+    #[test]
+    fn allow_overlaps() {
+        let p = PartialFunction::new()
+            .allow_overlaps()
+            .with(0.0, 10.0, Box::new(|x| 0.0))
+            .with(2.0, 4.0, Box::new(|x| 1.0))
+            .with(3.0, 5.0, Box::new(|x| 2.0))
+            .build();
+        assert_eq!(Some(0.0), p.eval(1.0));
+        assert_eq!(Some(1.0), p.eval(2.5));
+        assert_eq!(Some(2.0), p.eval(3.5));
+        assert_eq!(Some(0.0), p.eval(5.0));
+        assert_eq!(Some(0.0), p.eval(10.0));
+    }
+
+    #[test]
+    fn allow_overlaps_without_overlap() {
+        let builder = |overlaps: bool| {
+            let builder = if overlaps {
+                PartialFunction::new().allow_overlaps()
+            } else {
+                PartialFunction::new()
+            };
+            builder
+                .with_unbounded_lower(-1.0, Box::new(|x| -1.0))
+                .with(0.0, 1.0, Box::new(|x| x))
+                .with(1.0, 2.0, Box::new(|x| x * 2.0))
+                .with_point(2.0, Box::new(|x| 5.0))
+                .with(3.0, 4.0, Box::new(|x| x * 3.0))
+                .with_unbounded_upper(5.0, Box::new(|x| 6.0))
+        };
+        let plain = builder(false).build();
+        let layered = builder(true).build();
+        for bound in plain.breakpoints() {
+            for x in [bound - 0.5, *bound, bound + 0.5].iter() {
+                assert_eq!(plain.eval(*x), layered.eval(*x), "at {}", x);
+            }
+        }
+        assert_eq!(Some(12.0), layered.eval(4.0));
+        let plain = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with_unbounded_upper(5.0, Box::new(|x| 6.0))
+            .build();
+        let layered = PartialFunction::new()
+            .allow_overlaps()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with_unbounded_upper(5.0, Box::new(|x| 6.0))
+            .build();
+        assert_eq!(Some(1.0), plain.eval(1.0));
+        assert_eq!(Some(1.0), layered.eval(1.0));
+    }
+
+    #[test]
+    fn eval_many_segments() {
+        // Gaps, points and touching segments, checked against the layered search.
+        let builder = || {
+            (0..100).fold(PartialFunction::new(), |b, i| {
+                let x = f64::from(i) * 3.0;
+                b.with(x, x + 1.0, Box::new(move |_| i))
+                    .with(x + 1.0, x + 2.0, Box::new(move |_| -i))
+                    .with_point(x + 2.0, Box::new(move |_| 1000 + i))
+            })
+        };
+        let plain = builder().build();
+        let layered = builder().allow_overlaps().build();
+        let mirrored = builder().build().mirror(0.0);
+        let mirrored_layered = builder().allow_overlaps().build().mirror(0.0);
+        for i in -10..620 {
+            let x = f64::from(i) * 0.5;
+            assert_eq!(layered.eval(x), plain.eval(x), "at {}", x);
+            assert_eq!(mirrored_layered.eval(-x), mirrored.eval(-x), "at {}", -x);
+        }
+    }
+
+    #[test]
+    fn split_at() {
+        let (left, right) = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| x * 2.0))
+            .build()
+            .split_at(0.5);
+        assert_eq!(Some(0.25), left.eval(0.25));
+        assert_eq!(None, left.eval(0.5));
+        assert_eq!(None, left.eval(1.0));
+        assert_eq!(None, right.eval(0.25));
+        assert_eq!(Some(0.5), right.eval(0.5));
+        assert_eq!(Some(4.0), right.eval(2.0));
+    }
+
+    #[test]
+    fn split_at_boundary() {
+        let (left, right) = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| x * 2.0))
+            .build()
+            .split_at(2.0);
+        assert_eq!(Some(3.0), left.eval(1.5));
+        assert_eq!(None, left.eval(2.0));
+        assert_eq!(Some(4.0), right.eval(2.0));
+        assert_eq!(None, right.eval(1.5));
+    }
+
+    #[test]
+    fn validate() {
+        let builder = PartialFunction::new().with(0.0, 1.0, Box::new(|x| x)).with(
+            1.0,
+            2.0,
+            Box::new(|x| 5.0),
+        );
+        assert_eq!(Ok(()), builder.validate());
+        let builder = LowerPartialFunction::new()
+            .with(0.0, Box::new(|x| 1))
+            .with(1.0, Box::new(|x| 2));
+        assert_eq!(Ok(()), builder.validate());
+    }
+
+    #[test]
+    fn with_const() {
+        let p = PartialFunction::new()
+            .with_const(0.0, 1.0, String::from("low"))
+            .with(1.0, 2.0, Box::new(|x| String::from("high")))
+            .build();
+        assert_eq!(Some(String::from("low")), p.eval(0.5));
+        assert_eq!(Some(String::from("high")), p.eval(1.0));
+        let f = LowerPartialFunction::new()
+            .with_const(0.0, vec![1])
+            .with_const(1.0, vec![2])
+            .build();
+        assert_eq!(f.eval(0.5), Some(vec![1]));
+        assert_eq!(f.eval(3.0), Some(vec![2]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn with_const_overlap() {
+        PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with_const(0.5, 2.0, 5.0)
+            .build();
+    }
+
+    #[test]
+    fn nan_eval() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(2.0, 3.0, Box::new(|x| x))
+            .build();
+        assert!(p.eval(f64::NAN).is_none());
+        assert_eq!(vec![None], p.eval_sorted(&[f64::NAN]));
+    }
+
+    #[test]
+    fn nan_bounds() {
+        let builder = PartialFunction::new().with(0.0, 1.0, Box::new(|x| x));
+        assert!(!builder.can_insert(&f64::NAN, &2.0));
+        assert!(!builder.can_insert(&1.0, &f64::NAN));
+        assert!(!LowerPartialFunction::<f64, f64>::new().can_insert(&f64::NAN));
+        let error = builder.try_with(1.0, f64::NAN, Box::new(|x| x)).err();
+        assert_eq!(Some(InsertError::NaNBound(NaNBoundError)), error);
+    }
+
+    #[test]
+    #[should_panic(expected = "can't be NaN")]
+    fn nan_bounds_with() {
+        PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(f64::NAN, 2.0, Box::new(|x| x))
+            .build();
+    }
+
+    #[test]
+    #[should_panic(expected = "can't be NaN")]
+    fn lower_nan_bounds_with() {
+        LowerPartialFunction::new()
+            .with(f64::NAN, Box::new(|x| x))
+            .build();
+    }
+
+    #[test]
+    fn inverted_bounds() {
+        let builder = PartialFunction::new().with(0.0, 1.0, Box::new(|x| x));
+        assert!(!builder.can_insert(&3.0, &2.0));
+        let error = builder.try_with(3.0, 2.0, Box::new(|x| x)).err();
+        assert_eq!(
+            Some(InsertError::InvertedBounds {
+                lower: 3.0,
+                higher: 2.0
+            }),
+            error
+        );
+    }
+
+    #[test]
+    fn try_with() {
+        let p = PartialFunction::new()
+            .try_with(0.0, 1.0, Box::new(|x| x))
+            .unwrap()
+            .try_with(1.0, 2.0, Box::new(|x| 5.0))
+            .unwrap();
+        let error = p.try_with(0.5, 1.5, Box::new(|x| x)).err();
+        let overlap = OverlapError {
+            first: 0,
+            second: 2,
+            at: 0.5,
+        };
+        assert_eq!(Some(InsertError::Overlap(overlap)), error);
+        let f = LowerPartialFunction::new()
+            .try_with(0.0, Box::new(|x| 1))
+            .unwrap();
+        assert!(f.try_with(0.0, Box::new(|x| 2)).is_err());
+    }
+
+    #[test]
+    fn func_at() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| x * 2.0))
+            .build();
+        let f = p.func_at(&1.5).unwrap();
+        assert_eq!(2.0, f(1.0));
+        assert_eq!(3.0, f(1.5));
+        assert!(p.func_at(&3.0).is_none());
+    }
+
+    #[test]
+    fn lower_eval_with_bound() {
+        let f = LowerPartialFunction::new()
+            .with(0, Box::new(|x| 10))
+            .with(1000, Box::new(|x| 20))
+            .build();
+        assert_eq!(f.eval_with_bound(-1), None);
+        assert_eq!(f.eval_with_bound(5), Some((0, 10)));
+        assert_eq!(f.eval_with_bound(1000), Some((1000, 20)));
+        assert_eq!(f.eval_with_bound(5000), Some((1000, 20)));
+    }
+
+    #[test]
+    fn point() {
+        let p = PartialFunction::new()
+            .with(0.0, 0.5, Box::new(|x| x))
+            .with_point(0.5, Box::new(|x| 42.0))
+            .build();
+        assert_eq!(Some(0.25), p.eval(0.25));
+        assert_eq!(Some(42.0), p.eval(0.5));
+        assert_eq!(None, p.eval(0.75));
+        let xs = [0.25, 0.5, 0.75];
+        assert_eq!(vec![Some(0.25), Some(42.0), None], p.eval_sorted(&xs));
+    }
+
+    #[test]
+    fn point_between() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with_point(1.0, Box::new(|x| 42.0));
+        assert!(!p.can_insert(&1.0, &1.0));
+        assert!(!p.can_insert(&1.0, &2.0));
+        let p = PartialFunction::new()
+            .with_point(1.0, Box::new(|x| 42.0))
+            .with(0.0, 1.0, Box::new(|x| x))
+            .build();
+        assert_eq!(Some(42.0), p.eval(1.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn point_on_lower() {
+        PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with_point(0.0, Box::new(|x| 42.0))
+            .build();
+    }
+
+    #[test]
+    #[should_panic]
+    fn point_inside() {
+        PartialFunction::new()
+            .with_point(0.5, Box::new(|x| 42.0))
+            .with(0.0, 1.0, Box::new(|x| x))
+            .build();
+    }
+
+    #[test]
+    fn replace_func() {
+        let mut p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| 5.0))
+            .build();
+        assert!(p.replace_func(&1.0, &2.0, Box::new(|x| 6.0)));
+        assert!(!p.replace_func(&1.0, &3.0, Box::new(|x| 7.0)));
+        assert_eq!(Some(0.5), p.eval(0.5));
+        assert_eq!(Some(6.0), p.eval(1.5));
+    }
+
+    #[test]
+    fn eval_clamped() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| x * 2.0))
+            .build();
+        assert_eq!(Some(0.0), p.eval_clamped(-5.0));
+        assert_eq!(Some(0.5), p.eval_clamped(0.5));
+        assert_eq!(Some(4.0), p.eval_clamped(10.0));
+        assert_eq!(
+            None,
+            PartialFunction::<f64, f64>::new().build().eval_clamped(1.0)
+        );
+    }
+
+    /// Orders NaN above every other value.
+    fn nan_last(a: &f64, b: &f64) -> std::cmp::Ordering {
+        a.partial_cmp(b)
+            .unwrap_or_else(|| a.is_nan().cmp(&b.is_nan()))
+    }
+
+    #[test]
+    fn build_by() {
+        let p = PartialFunction::new()
+            .with(1.0, 2.0, Box::new(|x| 5.0))
+            .with(0.0, 1.0, Box::new(|x| 1.0))
+            .build_by(nan_last);
+        assert_eq!(Some(1.0), p.eval_by(0.5, nan_last));
+        assert_eq!(Some(5.0), p.eval_by(1.0, nan_last));
+        assert_eq!(None, p.eval_by(f64::NAN, nan_last));
+    }
+
+    #[test]
+    fn lower_build_by() {
+        let f = LowerPartialFunction::new()
+            .with(1.0, Box::new(|x| 2))
+            .with(0.0, Box::new(|x| 1))
+            .build_by(nan_last);
+        assert_eq!(f.eval_by(-1.0, nan_last), None);
+        assert_eq!(f.eval_by(0.5, nan_last), Some(1));
+        assert_eq!(f.eval_by(f64::NAN, nan_last), Some(2));
+    }
+
+    #[test]
+    fn unbounded() {
+        let p = PartialFunction::new()
+            .with_unbounded_lower(0.0, Box::new(|x| -1.0))
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with_unbounded_upper(2.0, Box::new(|x| 2.0))
+            .build();
+        assert_eq!(Some(-1.0), p.eval(-1e300));
+        assert_eq!(Some(0.0), p.eval(0.0));
+        assert_eq!(Some(0.5), p.eval(0.5));
+        assert_eq!(None, p.eval(1.5));
+        assert_eq!(Some(2.0), p.eval(2.0));
+        assert_eq!(Some(2.0), p.eval(1e300));
+        assert_eq!(vec![(1.0, 2.0)], p.gaps());
+    }
+
+    #[test]
+    fn gaps_layered() {
+        let p = PartialFunction::new()
+            .with(0.0, 10.0, Box::new(|x| x))
+            .with_priority(2.0, 3.0, 1, Box::new(|x| -x))
+            .with_priority(5.0, 6.0, 1, Box::new(|x| -x))
+            .with_priority(12.0, 13.0, 1, Box::new(|x| -x))
+            .build();
+        assert_eq!(Some(4.0), p.eval(4.0));
+        assert_eq!(vec![(10.0, 12.0)], p.gaps());
+    }
+
+    #[test]
+    fn unbounded_overlap() {
+        let builder = PartialFunction::new()
+            .with_unbounded_lower(0.0, Box::new(|x: f64| 0.0))
+            .with_unbounded_upper(2.0, Box::new(|x| 0.0));
+        assert!(builder.can_insert(&0.0, &2.0));
+        assert!(!builder.can_insert(&-1.0, &1.0));
+        assert!(!builder.can_insert(&1.0, &3.0));
+        assert!(!builder.can_insert(&2.0, &2.0));
+        assert!(!builder.can_insert_unbounded_lower(&-1.0));
+        assert!(!builder.can_insert_unbounded_upper(&3.0));
+        assert_eq!(
+            Err(InsertError::OverlapUnbounded {
+                lower: 1.0,
+                higher: 3.0
+            }),
+            builder.try_with(1.0, 3.0, Box::new(|x| 0.0)).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn unbounded_restrict() {
+        let p = PartialFunction::new()
+            .with_unbounded_lower(0.0, Box::new(|x| -1.0))
+            .with_unbounded_upper(0.0, Box::new(|x| 1.0))
+            .build()
+            .restrict(-2.0, 2.0);
+        assert_eq!(None, p.eval(-3.0));
+        assert_eq!(Some(-1.0), p.eval(-2.0));
+        assert_eq!(Some(1.0), p.eval(0.0));
+        assert_eq!(Some(1.0), p.eval(2.0));
+        assert_eq!(None, p.eval(3.0));
+    }
+
+    #[test]
+    fn unbounded_split_at() {
+        let (left, right) = PartialFunction::new()
+            .with_unbounded_lower(0.0, Box::new(|x| x))
+            .build()
+            .split_at(-1.0);
+        assert_eq!(Some(-5.0), left.eval(-5.0));
+        assert_eq!(None, left.eval(-1.0));
+        assert_eq!(Some(-0.5), right.eval(-0.5));
+        assert_eq!(None, right.eval(0.0));
+    }
+
+    #[test]
+    fn segments_roundtrip() {
+        let segments = PartialFunction::new()
+            .with(1.0, 2.0, Box::new(|x| 2.0))
+            .with(0.0, 1.0, Box::new(|x| 1.0))
+            .build()
+            .into_segments();
+        assert_eq!(2, segments.len());
+        assert_eq!(0.0, segments[0].lower);
+        let p = PartialFunction::from_segments(segments.into_iter().rev().collect()).unwrap();
+        assert_eq!(Some(1.0), p.eval(0.5));
+        assert_eq!(Some(2.0), p.eval(2.0));
+    }
+
+    #[test]
+    fn from_segments_overlap() {
+        let segments = vec![
+            DualBoundedFunction::new(Box::new(|x: f64| x), 0.0, 2.0),
+            DualBoundedFunction::new(Box::new(|x| x), 1.0, 3.0),
+        ];
+        let error = PartialFunction::from_segments(segments).err();
+        assert_eq!(
+            Some(InsertError::Overlap(OverlapError {
+                first: 0,
+                second: 1,
+                at: 1.0
+            })),
+            error
+        );
+    }
+
+    #[test]
+    fn from_iter() {
+        let p = (0..4)
+            .map(|i| {
+                let f: Box<dyn Fn(f64) -> f64> = Box::new(move |x| i as f64);
+                (i as f64, i as f64 + 1.0, f)
+            })
+            .collect::<PartialFunctionBuilder<_, _>>()
+            .build();
+        assert_eq!(Some(0.0), p.eval(0.5));
+        assert_eq!(Some(3.0), p.eval(4.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn extend_overlap() {
+        let f: Box<dyn Fn(f64) -> f64> = Box::new(|x| x);
+        PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .extend(vec![(0.5, 2.0, f)]);
+    }
+
+    #[test]
+    fn extend() {
+        let f: Box<dyn Fn(f64) -> f64> = Box::new(|x| 2.0);
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| 1.0))
+            .extend(vec![(1.0, 2.0, f)])
+            .build();
+        assert_eq!(Some(1.0), p.eval(0.5));
+        assert_eq!(Some(2.0), p.eval(1.5));
+    }
+
+    #[test]
+    fn lower_extend() {
+        let f: Box<dyn Fn(f64) -> i32> = Box::new(|x| 2);
+        let p = LowerPartialFunction::new()
+            .with(0.0, Box::new(|x| 1))
+            .extend(vec![(1.0, f)])
+            .build();
+        assert_eq!(Some(1), p.eval(0.5));
+        assert_eq!(Some(2), p.eval(1.5));
+    }
+
+    #[test]
+    fn lower_from_iter() {
+        let f = vec![1.0, 0.0]
+            .into_iter()
+            .map(|lower| {
+                let f: Box<dyn Fn(f64) -> f64> = Box::new(move |x| lower);
+                (lower, f)
+            })
+            .collect::<LowerPartialFunctionBuilder<_, _>>()
+            .build();
+        assert_eq!(f.eval(-1.0), None);
+        assert_eq!(f.eval(0.5), Some(0.0));
+        assert_eq!(f.eval(2.0), Some(1.0));
+    }
+
+    #[test]
+    fn profiled() {
+        let mut p = PartialFunction::new()
+            .with(1.0, 2.0, Box::new(|x| 2.0))
+            .with(0.0, 1.0, Box::new(|x| 1.0))
+            .build()
+            .profiled();
+        assert_eq!(Some(1.0), p.eval(0.5));
+        assert_eq!(Some(2.0), p.eval(1.0));
+        assert_eq!(Some(2.0), p.eval(2.0));
+        assert_eq!(None, p.eval(3.0));
+        assert_eq!(&[1, 2], p.hit_counts());
+    }
+
+    #[test]
+    fn into_iter_rebuild() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| 1.0))
+            .with(1.0, 2.0, Box::new(|x| 2.0))
+            .with(2.0, 3.0, Box::new(|x| 3.0))
+            .build()
+            .into_iter()
+            .filter(|b| b.lower != 2.0)
+            .map(|b| (b.lower, b.higher, b.func))
+            .collect::<PartialFunctionBuilder<_, _>>()
+            .build();
+        assert_eq!(Some(1.0), p.eval(0.5));
+        assert_eq!(Some(2.0), p.eval(2.0));
+        assert_eq!(None, p.eval(2.5));
+        assert_eq!(vec![0.0, 1.0, 2.0], p.breakpoints());
+    }
+
+    #[test]
+    fn lower_into_iter() {
+        let f = LowerPartialFunction::new()
+            .with(1.0, Box::new(|x| 2))
+            .with(0.0, Box::new(|x| 1))
+            .build();
+        let lowers = f.into_iter().map(|b| b.lower).collect::<Vec<_>>();
+        assert_eq!(vec![0.0, 1.0], lowers);
+    }
+
+    #[test]
+    fn borrowed_closures() {
+        let samples = (1..4).map(f64::from).collect::<Vec<_>>();
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| samples[0]))
+            .with(1.0, 2.0, Box::new(|x| samples[1] + samples[2]))
+            .build();
+        assert_eq!(Some(1.0), p.eval(0.5));
+        assert_eq!(Some(5.0), p.eval(1.5));
+        let lower = LowerPartialFunction::new()
+            .with(0.0, Box::new(|x| samples.len()))
+            .build();
+        assert_eq!(Some(3), lower.eval(1.0));
+    }
+
+    #[test]
+    fn eval_nearest() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(2.0, 3.0, Box::new(|x| x * 10.0))
+            .build();
+        assert_eq!(Some(0.5), p.eval_nearest(0.5));
+        assert_eq!(Some(1.0), p.eval_nearest(1.2));
+        assert_eq!(Some(1.0), p.eval_nearest(1.5));
+        assert_eq!(Some(20.0), p.eval_nearest(1.8));
+        assert_eq!(None, p.eval_nearest(-0.5));
+        assert_eq!(None, p.eval_nearest(3.5));
+        assert_eq!(None, p.eval_nearest(f64::NAN));
+    }
+
+    #[test]
+    fn eval_indexed() {
+        let p = PartialFunction::new()
+            .with(1.0, 2.0, Box::new(|x| 2.0))
+            .with(0.0, 1.0, Box::new(|x| 1.0))
+            .build();
+        assert_eq!(Some((0, 1.0)), p.eval_indexed(0.0));
+        assert_eq!(Some((1, 2.0)), p.eval_indexed(1.0));
+        assert_eq!(Some((1, 2.0)), p.eval_indexed(2.0));
+        assert_eq!(None, p.eval_indexed(3.0));
+    }
+
+    #[test]
+    fn eval_indexed_unbounded() {
+        let p = PartialFunction::new()
+            .with_unbounded_lower(0.0, Box::new(|x| -1.0))
+            .with(0.0, 1.0, Box::new(|x| 0.0))
+            .with_unbounded_upper(1.0, Box::new(|x| 1.0))
+            .build();
+        assert_eq!(Some((0, -1.0)), p.eval_indexed(-5.0));
+        assert_eq!(Some((1, 0.0)), p.eval_indexed(0.0));
+        assert_eq!(Some((2, 1.0)), p.eval_indexed(1.0));
+    }
+
+    #[test]
+    fn lower_eval_indexed() {
+        let f = LowerPartialFunction::new()
+            .with(1.0, Box::new(|x| 2))
+            .with(0.0, Box::new(|x| 1))
+            .build();
+        assert_eq!(None, f.eval_indexed(-1.0));
+        assert_eq!(Some((0, 1)), f.eval_indexed(0.5));
+        assert_eq!(Some((1, 2)), f.eval_indexed(1.0));
+    }
+
+    #[test]
+    fn segment_at() {
+        let p = PartialFunction::new()
+            .with(10, 20, Box::new(|x| panic!("evaluated")))
+            .with(20, 30, Box::new(|x| panic!("evaluated")))
+            .build();
+        assert_eq!(Some((&10, &20)), p.segment_at(&10));
+        assert_eq!(Some((&20, &30)), p.segment_at(&20));
+        assert_eq!(Some((&20, &30)), p.segment_at(&30));
+        assert_eq!(None::<(&i32, &i32)>, p.segment_at(&31));
+    }
+
+    #[test]
+    fn lower_segment_at() {
+        let f = LowerPartialFunction::new()
+            .with(1.0, Box::new(|x| 2))
+            .with(0.0, Box::new(|x| 1))
+            .build();
+        assert_eq!(None, f.segment_at(&-1.0));
+        assert_eq!(Some(&0.0), f.segment_at(&0.5));
+        assert_eq!(Some(&1.0), f.segment_at(&1.0));
+    }
+
+    #[test]
+    fn lower_labeled() {
+        let f = LowerPartialFunction::new_labeled()
+            .with_labeled(100, "gold", Box::new(|x| x - 100))
+            .with_labeled(0, "bronze", Box::new(|x| x))
+            .with_labeled(50, "silver", Box::new(|x| x - 50))
+            .build();
+        assert_eq!(None, f.eval_labeled(-1));
+        assert_eq!(Some((&"bronze", 10)), f.eval_labeled(10));
+        assert_eq!(Some((&"silver", 0)), f.eval_labeled(50));
+        assert_eq!(Some(&"gold"), f.label_at(&150));
+        assert_eq!(Some(50), f.eval(150));
+    }
+
+    #[test]
+    fn fill_gaps() {
+        let p = PartialFunction::new()
+            .with(1.0, 2.0, Box::new(|x| 1.0))
+            .with(3.0, 4.0, Box::new(|x| 3.0))
+            .fill_gaps(0.0, 5.0, 0.0)
+            .build();
+        assert_eq!(vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0], p.breakpoints());
+        assert_eq!(Some(0.0), p.eval(0.5));
+        assert_eq!(Some(1.0), p.eval(1.5));
+        assert_eq!(Some(0.0), p.eval(2.5));
+        assert_eq!(Some(3.0), p.eval(3.5));
+        assert_eq!(Some(0.0), p.eval(5.0));
+        assert!(p.gaps().is_empty());
+    }
+
+    #[test]
+    fn fill_gaps_inside() {
+        let p = PartialFunction::new()
+            .with(-1.0, 1.0, Box::new(|x| 1.0))
+            .with_point(2.0, Box::new(|x| 2.0))
+            .with(3.0, 6.0, Box::new(|x| 3.0))
+            .fill_gaps(0.0, 5.0, 0.0)
+            .build();
+        assert_eq!(vec![-1.0, 1.0, 2.0, 3.0, 6.0], p.breakpoints());
+        assert_eq!(Some(0.0), p.eval(1.5));
+        assert_eq!(Some(2.0), p.eval(2.0));
+    }
+
+    #[test]
+    fn eval_batch_indexed() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| x * 2.0))
+            .build();
+        let expected = vec![None, Some((0, 0.5)), Some((1, 2.0)), Some((1, 4.0)), None];
+        assert_eq!(expected, p.eval_batch_indexed(&[-1.0, 0.5, 1.0, 2.0, 3.0]));
+        let unsorted = p.eval_batch_indexed(&[2.0, 0.5]);
+        assert_eq!(vec![Some((1, 4.0)), Some((0, 0.5))], unsorted);
+    }
+
+    #[test]
+    fn eval_batch_indexed_unsorted() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| x * 2.0))
+            .with_point(3.0, Box::new(|x| -x))
+            .with(4.0, 5.0, Box::new(|x| x * 3.0))
+            .build();
+        let xs = [
+            4.5,
+            0.0,
+            3.0,
+            2.5,
+            5.0,
+            1.0,
+            -1.0,
+            2.0,
+            0.5,
+            3.0,
+            f64::NAN,
+            4.0,
+        ];
+        let expected = xs.iter().map(|&x| p.eval_indexed(x)).collect::<Vec<_>>();
+        assert_eq!(expected, p.eval_batch_indexed(&xs));
+        assert_eq!(Some((2, -3.0)), p.eval_batch_indexed(&xs)[2]);
+        assert_eq!(None, p.eval_batch_indexed(&xs)[10]);
+    }
+
+    #[test]
+    fn const_eval_ref() {
+        let p = PartialConstFunction::new()
+            .with(1.0, 2.0, vec![2; 100])
+            .with(0.0, 1.0, vec![1; 100])
+            .build();
+        assert_eq!(None, p.eval_ref(&-0.5));
+        assert_eq!(Some(&vec![1; 100]), p.eval_ref(&0.5));
+        assert_eq!(Some(&vec![2; 100]), p.eval_ref(&1.0));
+        assert_eq!(Some(&vec![2; 100]), p.eval_ref(&2.0));
+        assert_eq!(None, p.eval_ref(&2.5));
+    }
+
+    #[test]
+    fn zip() {
+        let position = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x * 10.0))
+            .with(1.0, 3.0, Box::new(|x| 10.0))
+            .build();
+        let opacity = PartialFunction::new()
+            .with(0.5, 2.0, Box::new(|x| 1))
+            .build();
+        let p = position.zip(opacity);
+        assert_eq!(None, p.eval(0.0));
+        assert_eq!(Some((5.0, 1)), p.eval(0.5));
+        assert_eq!(Some((10.0, 1)), p.eval(2.0));
+        assert_eq!(None, p.eval(2.5));
+    }
+
+    #[test]
+    fn zip_with() {
+        let a = PartialFunction::new().with(0, 10, Box::new(|x| x)).build();
+        let b = PartialFunction::new()
+            .with(5, 20, Box::new(|x| x * 2))
+            .build();
+        let p = a.zip_with(b, |a, b| a + b);
+        assert_eq!(None, p.eval(4));
+        assert_eq!(Some(15), p.eval(5));
+        assert_eq!(Some(30), p.eval(10));
+        assert_eq!(None, p.eval(11));
+    }
+
+    #[test]
+    fn build_total() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .build_total(Box::new(|x| -1.0));
+        assert_eq!(0.5, p.eval(0.5));
+        assert_eq!(1.0, p.eval(1.0));
+        assert_eq!(-1.0, p.eval(2.0));
+        assert_eq!(-1.0, p.eval(f64::NAN));
+        assert_eq!(Some(0.5), p.partial().eval(0.5));
+    }
+
+    #[test]
+    fn lower_eval_ord() {
+        let tiers = (0..1000)
+            .map(|i| {
+                let f: Box<dyn Fn(i32) -> i32> = Box::new(move |_| i);
+                (i * 10, f)
+            })
+            .collect::<LowerPartialFunctionBuilder<_, _>>()
+            .build();
+        assert_eq!(tiers.eval_ord(-1), None);
+        for x in [0, 9, 10, 15, 5000, 9990, 100000].iter() {
+            assert_eq!(tiers.eval_ord(*x), tiers.eval(*x));
+        }
+        assert_eq!(tiers.eval_ord(15), Some(1));
+        let empty = LowerPartialFunction::<i32, i32>::new().build();
+        assert_eq!(empty.eval_ord(0), None);
+    }
+
+    #[test]
+    fn from_sorted_segments() {
+        let segments = |bounds: &[(f64, f64)]| {
+            bounds
+                .iter()
+                .map(|&(l, h)| DualBoundedFunction::new(Box::new(|x: f64| x), l, h))
+                .collect::<Vec<_>>()
+        };
+        let f =
+            PartialFunction::from_sorted_segments(segments(&[(0.0, 1.0), (1.0, 1.0), (2.0, 3.0)]))
+                .unwrap();
+        assert_eq!(f.eval(1.0), Some(1.0));
+        assert_eq!(f.eval(3.0), Some(3.0));
+        let unchecked =
+            PartialFunction::from_sorted_segments_unchecked(segments(&[(0.0, 1.0), (1.0, 2.0)]));
+        assert_eq!(unchecked.eval(1.5), Some(1.5));
+        match PartialFunction::from_sorted_segments(segments(&[(0.0, 2.0), (1.0, 3.0)])) {
+            Err(InsertError::Overlap(e)) => assert_eq!((e.first, e.second, e.at), (0, 1, 1.0)),
+            _ => panic!("expected an overlap"),
+        }
+        assert!(
+            PartialFunction::from_sorted_segments(segments(&[(2.0, 3.0), (0.0, 1.0)])).is_err()
+        );
+        assert!(
+            PartialFunction::from_sorted_segments(segments(&[(1.0, 1.0), (1.0, 2.0)])).is_err()
+        );
+        assert!(PartialFunction::from_sorted_segments(segments(&[(1.0, 0.0)])).is_err());
+    }
+
+    #[test]
+    fn lower_from_sorted_segments() {
+        let segments = |bounds: &[i32]| {
+            bounds
+                .iter()
+                .map(|&l| LowerBoundedFunction::new(Box::new(move |_: i32| l), l))
+                .collect::<Vec<_>>()
+        };
+        let f = LowerPartialFunction::from_sorted_segments(segments(&[0, 10, 20])).unwrap();
+        assert_eq!(f.eval(15), Some(10));
+        let unchecked = LowerPartialFunction::from_sorted_segments_unchecked(segments(&[0, 10]));
+        assert_eq!(unchecked.eval(5), Some(0));
+        match LowerPartialFunction::from_sorted_segments(segments(&[0, 10, 10])) {
+            Err(InsertError::Overlap(e)) => assert_eq!((e.first, e.second, e.at), (1, 2, 10)),
+            _ => panic!("expected an overlap"),
+        }
+    }
+
+    #[test]
+    fn touching_chain() {
+        let segments = [(0.0, 1.0, 0.0), (1.0, 2.0, 1.0), (2.0, 3.0, 2.0)];
+        for order in [[0, 1, 2], [2, 1, 0], [1, 2, 0]].iter() {
+            let p = order
+                .iter()
+                .map(|&i| {
+                    let (l, h, v) = segments[i];
+                    let f: Box<dyn Fn(f64) -> f64> = Box::new(move |_| v);
+                    (l, h, f)
+                })
+                .collect::<PartialFunctionBuilder<_, _>>()
+                .build();
+            assert_eq!(p.eval(0.0), Some(0.0));
+            assert_eq!(p.eval(1.0), Some(1.0));
+            assert_eq!(p.eval(2.0), Some(2.0));
+            assert_eq!(p.eval(3.0), Some(2.0));
+            assert_eq!(p.eval(3.5), None);
+        }
+    }
+
+    #[test]
+    fn gaps_undefined() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(2.0, 3.0, Box::new(|x| x * 10.0))
+            .build();
+        assert_eq!(None, p.eval(-5.0));
+        assert_eq!(Some(0.0), p.eval(0.0));
+        assert_eq!(None, p.eval(1.0));
+        assert_eq!(None, p.eval(1.5));
+        assert_eq!(Some(20.0), p.eval(2.0));
+        assert_eq!(Some(30.0), p.eval(3.0));
+        assert_eq!(None, p.eval(3.5));
+        assert_eq!(
+            vec![None, None, Some(20.0), None],
+            p.eval_sorted(&[-5.0, 1.5, 2.0, 3.5])
+        );
+    }
+
+    #[test]
+    fn sorted_unchecked_roundtrip() {
+        let p = PartialFunction::new()
+            .with(2.0, 3.0, Box::new(|x| x * 10.0))
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| -x))
+            .build();
+        let segments = p.into_segments();
+        let bounds = segments
+            .iter()
+            .map(|b| (b.lower, b.higher))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![(0.0, 1.0), (1.0, 2.0), (2.0, 3.0)], bounds);
+        let p = PartialFunction::from_sorted_segments_unchecked(segments);
+        assert_eq!(Some(-1.0), p.eval(1.0));
+        assert_eq!(Some(30.0), p.eval(3.0));
+        assert_eq!(&[0.0, 1.0, 2.0, 3.0], p.breakpoints());
+    }
+
+    #[test]
+    fn default_empty() {
+        #[derive(Default)]
+        struct Holder {
+            func: PartialFunction<'static, f32, f32>,
+            lower: LowerPartialFunction<'static, f32, f32>,
+        }
+        let h = Holder::default();
+        assert!(h.func.is_empty());
+        assert_eq!(0, h.func.len());
+        assert!(h.lower.is_empty());
+        for x in [f32::NEG_INFINITY, -1.0, 0.0, 1.0, f32::INFINITY, f32::NAN].iter() {
+            assert_eq!(None, h.func.eval(*x));
+            assert_eq!(None, h.lower.eval(*x));
+        }
+        let built = PartialFunctionBuilder::default()
+            .with(0.0, 1.0, Box::new(|x: f32| x))
+            .with_unbounded_upper(1.0, Box::new(|x| x))
+            .build();
+        assert_eq!(2, built.len());
+        let lower = LowerPartialFunctionBuilder::<_, _>::default()
+            .with(0.0, Box::new(|x: f32| x))
+            .build();
+        assert_eq!(1, lower.len());
+    }
+
+    #[test]
+    fn fold_segments() {
+        let p = PartialFunction::new()
+            .with(2.0, 5.0, Box::new(|x| x))
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with_unbounded_upper(6.0, Box::new(|x| x))
+            .build();
+        let covered = p.fold_segments(0.0, |acc, l, h| acc + (h - l));
+        assert_eq!(4.0, covered);
+        let widest = p.fold_segments(0.0, |acc: f64, l, h| acc.max(h - l));
+        assert_eq!(3.0, widest);
+        let lowers = p.fold_segments(vec![], |mut acc, l, _| {
+            acc.push(*l);
+            acc
+        });
+        assert_eq!(vec![0.0, 2.0], lowers);
+    }
+
+    #[test]
+    fn context_eval() {
+        let p = ContextPartialFunction::new()
+            .with(
+                1.0,
+                2.0,
+                Box::new(|x, calls: &mut Vec<f64>| {
+                    calls.push(x);
+                    2.0
+                }),
+            )
+            .with(
+                0.0,
+                1.0,
+                Box::new(|x, calls: &mut Vec<f64>| {
+                    calls.push(x);
+                    1.0
+                }),
+            )
+            .build();
+        let mut calls = vec![];
+        assert_eq!(Some(1.0), p.eval(0.5, &mut calls));
+        assert_eq!(Some(2.0), p.eval(1.0, &mut calls));
+        assert_eq!(None, p.eval(3.0, &mut calls));
+        assert_eq!(vec![0.5, 1.0], calls);
+    }
+
+    #[test]
+    fn context_lower_eval() {
+        let p = ContextLowerPartialFunction::new()
+            .with(
+                10,
+                Box::new(|x, total: &mut i32| {
+                    *total += x;
+                    2
+                }),
+            )
+            .with(
+                0,
+                Box::new(|x, total: &mut i32| {
+                    *total += x;
+                    1
+                }),
+            )
+            .build();
+        let mut total = 0;
+        assert_eq!(None, p.eval(-1, &mut total));
+        assert_eq!(Some(1), p.eval(5, &mut total));
+        assert_eq!(Some(2), p.eval(15, &mut total));
+        assert_eq!(20, total);
+    }
+
+    #[test]
+    fn edge_distance() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 4.0, Box::new(|x| x))
+            .with_unbounded_upper(5.0, Box::new(|x| x))
+            .build();
+        assert_eq!(Some(0.25), p.edge_distance(0.25));
+        assert_eq!(Some(0.0), p.edge_distance(1.0));
+        assert_eq!(Some(1.0), p.edge_distance(3.0));
+        assert_eq!(Some(10.0), p.edge_distance(15.0));
+        assert_eq!(None, p.edge_distance(4.5));
+        assert_eq!(None, p.edge_distance(-1.0));
+    }
+
+    #[test]
+    fn lower_priority() {
+        let builder = LowerPartialFunction::new()
+            .with(0, Box::new(|x| 0))
+            .with_priority(0, 5, Box::new(|x| 5))
+            .with_priority(0, -1, Box::new(|x| -1))
+            .with(10, Box::new(|x| 10));
+        assert!(!builder.can_insert(&0));
+        assert!(!builder.can_insert_priority(&0, 5));
+        assert!(builder.can_insert_priority(&0, 6));
+        assert!(builder.validate().is_ok());
+        let p = builder.build();
+        assert_eq!(None, p.eval(-1));
+        assert_eq!(Some(5), p.eval(0));
+        assert_eq!(Some(5), p.eval(9));
+        assert_eq!(Some(10), p.eval(10));
+        assert_eq!(vec![Some(5), Some(10)], p.eval_sorted(&[5, 15]));
+        assert_eq!(Some(5), p.eval_ord(5));
+    }
+
+    #[test]
+    fn segment_fn() {
+        let p = SegmentPartialFunction::new()
+            .with(
+                1.0,
+                2.0,
+                SegmentFn::Linear {
+                    slope: 2.0,
+                    intercept: 1.0,
+                },
+            )
+            .with(0.0, 1.0, SegmentFn::Const(5.0))
+            .with(2.0, 3.0, SegmentFn::Identity)
+            .with(3.0, 4.0, SegmentFn::Custom(Box::new(|x| x * x)))
+            .build();
+        assert_eq!(None, p.eval(-1.0));
+        assert_eq!(Some(5.0), p.eval(0.5));
+        assert_eq!(Some(4.0), p.eval(1.5));
+        assert_eq!(Some(2.5), p.eval(2.5));
+        assert_eq!(Some(16.0), p.eval(4.0));
+        assert_eq!(None, p.eval(4.5));
+        let widened = SegmentPartialFunction::new()
+            .with(
+                0,
+                10,
+                SegmentFn::Linear {
+                    slope: 0.5,
+                    intercept: 0.0,
+                },
+            )
+            .build();
+        assert_eq!(Some(2.5), widened.eval(5));
+    }
+
+    #[test]
+    fn segment_fn_bounds() {
+        let builder = SegmentPartialFunction::new()
+            .with(1.0, 1.0, SegmentFn::Const(2.0))
+            .with(0.0, 1.0, SegmentFn::Identity);
+        assert!(!builder.can_insert(&0.5, &2.0));
+        assert!(builder.can_insert(&1.5, &2.0));
+        let p = builder.build();
+        assert_eq!(Some(0.5), p.eval(0.5));
+        assert_eq!(Some(2.0), p.eval(1.0));
+        assert_eq!(None, p.eval(f64::NAN));
+    }
+
+    #[test]
+    #[should_panic]
+    fn segment_fn_overlap() {
+        SegmentPartialFunction::new()
+            .with(0.0, 2.0, SegmentFn::Const(0.0))
+            .with(1.0, 3.0, SegmentFn::Const(1.0));
+    }
+
+    #[test]
+    fn segment_samples() {
+        let p = PartialFunction::new()
+            .with(2.0, 4.0, Box::new(|x| x * 10.0))
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(5.0, 5.0, Box::new(|x| -x))
+            .with_unbounded_upper(6.0, Box::new(|x| x))
+            .build();
+        let samples = p.segment_samples().collect::<Vec<_>>();
+        assert_eq!(vec![(0.5, 0.5), (3.0, 30.0), (5.0, -5.0)], samples);
+    }
+
+    #[test]
+    fn is_continuous() {
+        let single = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .build();
+        assert!(single.is_continuous());
+        let touching = PartialFunction::new()
+            .with(1.0, 2.0, Box::new(|x| x))
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with_unbounded_upper(2.0, Box::new(|x| x))
+            .build();
+        assert!(touching.is_continuous());
+        let gapped = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.5, 2.0, Box::new(|x| x))
+            .build();
+        assert!(!gapped.is_continuous());
+        let layered = PartialFunction::new()
+            .with(0.0, 10.0, Box::new(|x| x))
+            .with_priority(2.0, 3.0, 1, Box::new(|x| -x))
+            .with_priority(5.0, 6.0, 1, Box::new(|x| -x))
+            .build();
+        assert!(layered.is_continuous());
+    }
+
+    #[test]
+    fn cloneable() {
+        let base = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| x * 2.0))
+            .with_unbounded_upper(3.0, Box::new(|x| -x))
+            .build()
+            .cloneable();
+        let copy = base.clone();
+        let shifted = copy.clone().into_partial().shift(10.0);
+        for x in [-1.0, 0.0, 0.5, 1.0, 2.0, 2.5, 3.0, 10.0].iter() {
+            assert_eq!(base.eval(*x), copy.eval(*x));
+            assert_eq!(base.eval(*x), shifted.eval(*x + 10.0));
+        }
+        assert_eq!(Some(3.0), copy.eval(1.5));
+        drop(base);
+        assert_eq!(Some(-4.0), copy.eval(4.0));
+    }
+
+    #[test]
+    fn mirror() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 3.0, Box::new(|x| x * 10.0))
+            .build()
+            .mirror(5.0);
+        assert_eq!(&[7.0, 9.0, 10.0], p.breakpoints());
+        assert_eq!(Some(1.0), p.eval(9.0));
+        assert_eq!(Some(0.0), p.eval(10.0));
+        assert_eq!(Some(30.0), p.eval(7.0));
+        assert_eq!(Some(20.0), p.eval(8.0));
+        assert_eq!(None, p.eval(6.0));
+    }
+
+    #[test]
+    fn eval_transpose() {
+        let evaluated = std::cell::Cell::new(0);
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(Ok))
+            .with(
+                1.0,
+                2.0,
+                Box::new(|x| {
+                    evaluated.set(evaluated.get() + 1);
+                    Err("degenerate")
+                }),
+            )
+            .with(2.0, 3.0, Box::new(|x| Ok(x * 2.0)))
+            .build();
+        assert_eq!(Ok(Some(0.5)), p.eval_transpose(0.5));
+        assert_eq!(Ok(Some(5.0)), p.eval_transpose(2.5));
+        assert_eq!(Ok(None), p.eval_transpose(4.0));
+        assert_eq!(0, evaluated.get());
+        assert_eq!(Err("degenerate"), p.eval_transpose(1.5));
+        assert_eq!(1, evaluated.get());
+    }
+
+    #[test]
+    fn with_many() {
+        let segments = |bounds: &[(f64, f64)]| {
+            bounds
+                .iter()
+                .map(|&(l, h)| {
+                    let f: Box<dyn Fn(f64) -> f64> = Box::new(move |_| l);
+                    (l, h, f)
+                })
+                .collect::<Vec<_>>()
+        };
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|_| -1.0))
+            .with_many(segments(&[(3.0, 4.0), (1.0, 2.0), (2.0, 2.0)]))
+            .unwrap()
+            .build();
+        assert_eq!(Some(-1.0), p.eval(0.5));
+        assert_eq!(Some(1.0), p.eval(1.5));
+        assert_eq!(Some(2.0), p.eval(2.0));
+        assert_eq!(Some(3.0), p.eval(4.0));
+        let error = PartialFunction::new()
+            .with(0.0, 10.0, Box::new(|_| -1.0))
+            .with_many(segments(&[
+                (12.0, 13.0),
+                (11.0, 20.0),
+                (5.0, 6.0),
+                (10.0, 11.0),
+                (10.0, 10.0),
+            ]))
+            .err()
+            .unwrap();
+        let overlaps = error
+            .errors
+            .iter()
+            .map(|e| match e {
+                InsertError::Overlap(o) => (o.first, o.second),
+                _ => panic!("expected overlaps only"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(vec![(0, 3), (4, 5), (1, 2)], overlaps);
+        let error = PartialFunction::new()
+            .with_many(segments(&[(1.0, 0.0), (f64::NAN, 1.0)]))
+            .err()
+            .unwrap();
+        assert_eq!(2, error.errors.len());
+    }
+
+    #[test]
+    fn lower_with_many() {
+        let segments = |bounds: &[i32]| {
+            bounds
+                .iter()
+                .map(|&l| {
+                    let f: Box<dyn Fn(i32) -> i32> = Box::new(move |_| l);
+                    (l, f)
+                })
+                .collect::<Vec<_>>()
+        };
+        let p = LowerPartialFunction::new()
+            .with(0, Box::new(|_| -1))
+            .with_many(segments(&[20, 10]))
+            .unwrap()
+            .build();
+        assert_eq!(Some(-1), p.eval(5));
+        assert_eq!(Some(10), p.eval(15));
+        assert_eq!(Some(20), p.eval(25));
+        let error = LowerPartialFunction::new()
+            .with(0, Box::new(|_| -1))
+            .with_many(segments(&[10, 0, 10]))
+            .err()
+            .unwrap();
+        assert_eq!(
+            vec![
+                InsertError::Overlap(OverlapError {
+                    first: 0,
+                    second: 2,
+                    at: 0
+                }),
+                InsertError::Overlap(OverlapError {
+                    first: 1,
+                    second: 3,
+                    at: 10
+                }),
+            ],
+            error.errors
+        );
+    }
+
+    #[test]
+    fn eval_and() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| vec![x; 100]))
+            .build();
+        assert_eq!(Some(100), p.eval_and(0.5, |v| v.len()));
+        assert_eq!(Some(true), p.eval_and(0.5, |v| v[0] == 0.5));
+        assert_eq!(None, p.eval_and(2.0, |v| v.len()));
+    }
+
+    #[test]
+    fn partial_function_2d() {
+        let surface = PartialFunction2D::new()
+            .with(
+                0.0,
+                1.0,
+                PartialFunction::new().with(0.0, 10.0, Box::new(|y| y)),
+            )
+            .with(
+                1.0,
+                2.0,
+                PartialFunction::new()
+                    .with(0.0, 1.0, Box::new(|y| -y))
+                    .with(5.0, 6.0, Box::new(|y| y * 2.0)),
+            )
+            .build();
+        assert_eq!(Some(5.0), surface.eval2(0.5, 5.0));
+        assert_eq!(Some(-0.5), surface.eval2(1.0, 0.5));
+        assert_eq!(Some(12.0), surface.eval2(1.5, 6.0));
+        assert_eq!(None, surface.eval2(1.5, 3.0));
+        assert_eq!(None, surface.eval2(3.0, 0.5));
+    }
+
+    #[test]
+    fn sync_eval() {
+        let p = SyncPartialFunction::new()
+            .with(1.0, 2.0, Box::new(|x| x * 2.0))
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(2.0, 2.0, Box::new(|x| -1.0))
+            .with(3.0, 4.0, Box::new(|x| 5.0))
+            .build();
+        assert_eq!(None, p.eval(-1.0));
+        assert_eq!(Some(0.5), p.eval(0.5));
+        assert_eq!(Some(2.0), p.eval(1.0));
+        assert_eq!(Some(-1.0), p.eval(2.0));
+        assert_eq!(None, p.eval(2.5));
+        assert_eq!(Some(5.0), p.eval(4.0));
+        assert_eq!(None, p.eval(f64::NAN));
+        let shared = std::sync::Arc::new(p);
+        let other = std::sync::Arc::clone(&shared);
+        let y = std::thread::spawn(move || other.eval(1.5)).join().unwrap();
+        assert_eq!(Some(3.0), y);
+        assert_eq!(vec![Some(0.5), None], shared.eval_all(&[0.5, 2.5]));
+    }
+
+    #[test]
+    fn lower_with_replace() {
+        let defaults = LowerPartialFunction::new()
+            .with(0, Box::new(|x| 0))
+            .with(10, Box::new(|x| 10));
+        assert!(defaults.contains_lower(&10));
+        assert!(!defaults.contains_lower(&20));
+        let p = defaults
+            .with_replace(10, Box::new(|x| 11))
+            .with_replace(20, Box::new(|x| 20))
+            .with_replace(10, Box::new(|x| 12))
+            .build();
+        assert_eq!(Some(0), p.eval(5));
+        assert_eq!(Some(12), p.eval(10));
+        assert_eq!(Some(20), p.eval(25));
+        assert_eq!(3, p.len());
+    }
+
+    #[test]
+    fn lower_with_replace_priority() {
+        let p = LowerPartialFunction::new()
+            .with(0, Box::new(|x| 0))
+            .with_priority(10, 1, Box::new(|x| 100))
+            .with_priority(20, -1, Box::new(|x| -1))
+            .with_replace(10, Box::new(|x| 11))
+            .with_replace(10, Box::new(|x| 12))
+            .with_replace(20, Box::new(|x| 21))
+            .build();
+        assert_eq!(Some(0), p.eval(5));
+        assert_eq!(Some(100), p.eval(10));
+        assert_eq!(Some(21), p.eval(20));
+    }
+
+    #[test]
+    #[should_panic(expected = "segment [1.5, 3.0) overlaps segment 1 [1.0, 2.0) of priority 0")]
+    fn overlap_panic_message() {
+        PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| x))
+            .with(1.5, 3.0, Box::new(|x| x));
+    }
+
+    #[test]
+    fn rectangles() {
+        let builder = PartialFunction2::new()
+            .with(
+                0,
+                10,
+                0,
+                5,
+                Box::new(|level, difficulty| level + difficulty),
+            )
+            .with(
+                0,
+                10,
+                5,
+                10,
+                Box::new(|level, difficulty| level * difficulty),
+            )
+            .with(10, 20, 0, 10, Box::new(|level, difficulty| -1));
+        assert!(!builder.can_insert(&5, &15, &8, &12));
+        assert!(!builder.can_insert(&20, &20, &0, &10));
+        assert!(builder.can_insert(&20, &30, &0, &10));
+        let f = builder.build();
+        assert_eq!(3, f.len());
+        assert_eq!(Some(3), f.eval(1, 2));
+        assert_eq!(Some(10), f.eval(2, 5));
+        assert_eq!(Some(-1), f.eval(10, 9));
+        assert_eq!(None, f.eval(10, 10));
+        assert_eq!(None, f.eval(20, 0));
+        assert_eq!(None, f.eval(-1, 0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn rectangles_overlap() {
+        PartialFunction2::new()
+            .with(0.0, 1.0, 0.0, 1.0, Box::new(|x, y| x + y))
+            .with(0.5, 2.0, 0.5, 2.0, Box::new(|x, y| x * y));
+    }
+
+    #[test]
+    fn ref_function() {
+        let configs = [String::from("easy"), String::from("hard")];
+        let found = {
+            let f: PartialRefFunction<i32, String> = PartialConstFunction::new()
+                .with(0, 10, &configs[0])
+                .with(10, 20, &configs[1])
+                .build();
+            assert_eq!(None, f.eval(25));
+            f.eval(15)
+        };
+        // The reference outlives the function.
+        assert_eq!(Some(&configs[1]), found);
+    }
+
+    #[test]
+    fn build_is_stable() {
+        let p = PartialFunction::new()
+            .allow_overlaps()
+            .with(1.0, 2.0, Box::new(|x| 3.0))
+            .with(0.0, 1.0, Box::new(|x| 0.0))
+            .with(1.0, 3.0, Box::new(|x| 1.0))
+            .with(1.0, 1.5, Box::new(|x| 2.0))
+            .build();
+        // The most recently inserted segment is used where they overlap.
+        assert_eq!(Some(0.0), p.eval(0.5));
+        assert_eq!(Some(2.0), p.eval(1.0));
+        assert_eq!(Some(1.0), p.eval(2.5));
+        let segments = p
+            .into_iter()
+            .map(|s| (s.lower, s.higher, (s.func)(s.lower)))
+            .collect::<Vec<_>>();
+        // Segments sharing a lower bound keep their insertion order.
+        assert_eq!(
+            vec![
+                (0.0, 1.0, 0.0),
+                (1.0, 2.0, 3.0),
+                (1.0, 3.0, 1.0),
+                (1.0, 1.5, 2.0)
+            ],
+            segments
+        );
+    }
+
+    #[test]
+    fn try_eval() {
+        let p = PartialFunction::new()
+            .with(0.0, 10.0, Box::new(|x| x))
+            .with(20.0, 30.0, Box::new(|x| -x))
+            .build();
+        assert_eq!(Ok(5.0), p.try_eval(5.0));
+        assert_eq!(Ok(-30.0), p.try_eval(30.0));
+        assert_eq!(Err(EvalError::BelowDomain { min: 0.0 }), p.try_eval(-1.0));
+        assert_eq!(Err(EvalError::AboveDomain { max: 30.0 }), p.try_eval(37.0));
+        let gap = EvalError::InGap {
+            gap_start: 10.0,
+            gap_end: 20.0,
+        };
+        assert_eq!(Err(gap.clone()), p.try_eval(10.0));
+        assert_eq!(Err(gap), p.try_eval(15.0));
+        assert_eq!(Err(EvalError::NaN), p.try_eval(f64::NAN));
+        for i in -10..40 {
+            let x = i as f64;
+            assert_eq!(p.eval(x), p.try_eval(x).ok());
+        }
+        let empty = PartialFunction::<f64, f64>::default();
+        assert_eq!(Err(EvalError::Empty), empty.try_eval(1.0));
+    }
+
+    #[test]
+    fn lower_try_eval() {
+        let p = LowerPartialFunction::new().with(0, Box::new(|x| x)).build();
+        assert_eq!(Ok(5), p.try_eval(5));
+        assert_eq!(Err(EvalError::BelowDomain { min: 0 }), p.try_eval(-1));
+        let empty = LowerPartialFunction::<i32, i32>::default();
+        assert_eq!(Err(EvalError::Empty), empty.try_eval(1));
+    }
+
+    #[test]
+    fn lower_try_eval_incomparable() {
+        // Ordered component-wise, so pairs growing in different directions can't be compared.
+        #[derive(Clone, Debug, PartialEq)]
+        struct Pair(i32, i32);
+        impl PartialOrd for Pair {
+            fn partial_cmp(&self, other: &Pair) -> Option<std::cmp::Ordering> {
+                use std::cmp::Ordering::Equal;
+                match (self.0.cmp(&other.0), self.1.cmp(&other.1)) {
+                    (a, b) if a == b => Some(a),
+                    (a, Equal) | (Equal, a) => Some(a),
+                    _ => None,
+                }
+            }
+        }
+        let p = LowerPartialFunction::new()
+            .with(Pair(0, 0), Box::new(|x| 1))
+            .build();
+        assert_eq!(Ok(1), p.try_eval(Pair(1, 0)));
+        assert_eq!(
+            Err(EvalError::BelowDomain { min: Pair(0, 0) }),
+            p.try_eval(Pair(-1, 0))
+        );
+        assert_eq!(Err(EvalError::Incomparable), p.try_eval(Pair(-1, 1)));
+    }
+
+    #[test]
+    fn segment_accessors() {
+        let p = PartialFunction::new()
+            .with(5.0, 8.0, Box::new(|x| x))
+            .with(-2.0, 0.0, Box::new(|x| x))
+            .with(1.0, 3.0, Box::new(|x| x))
+            .build();
+        assert_eq!(3, p.len());
+        assert!(!p.is_empty());
+        assert_eq!(Some((&-2.0, &0.0)), p.first_segment());
+        assert_eq!(Some((&5.0, &8.0)), p.last_segment());
+        let empty = PartialFunction::<f64, f64>::default();
+        assert_eq!(None, empty.first_segment());
+        assert_eq!(None, empty.last_segment());
+
+        let lower = LowerPartialFunction::new()
+            .with(10, Box::new(|x| x))
+            .with(-5, Box::new(|x| x))
+            .with(0, Box::new(|x| x))
+            .build();
+        assert_eq!(3, lower.len());
+        assert!(!lower.is_empty());
+        assert_eq!(Some(&-5), lower.first_lower());
+        assert_eq!(Some(&10), lower.last_lower());
+        let empty = LowerPartialFunction::<i32, i32>::default();
+        assert_eq!(None, empty.first_lower());
+        assert_eq!(None, empty.last_lower());
+    }
+
+    #[test]
+    fn combine() {
+        let base = PartialFunction::new()
+            .with(0.0, 10.0, Box::new(|x| x))
+            .with(12.0, 20.0, Box::new(|x| 1.0))
+            .build();
+        let modifier = PartialFunction::new()
+            .with(5.0, 15.0, Box::new(|x| 2.0))
+            .with_point(18.0, Box::new(|x| 3.0))
+            .build();
+        let sum = base.combine(&modifier, |a, b| a + b);
+        assert_eq!(None, sum.eval(4.0));
+        assert_eq!(Some(7.0), sum.eval(5.0));
+        assert_eq!(Some(11.5), sum.eval(9.5));
+        assert_eq!(None, sum.eval(10.0));
+        assert_eq!(None, sum.eval(11.0));
+        assert_eq!(Some(3.0), sum.eval(12.0));
+        assert_eq!(None, sum.eval(15.0));
+        assert_eq!(Some(4.0), sum.eval(18.0));
+        assert_eq!(None, sum.eval(19.0));
+        for i in 0..=80 {
+            let x = i as f64 * 0.25;
+            let expected = match (base.eval(x), modifier.eval(x)) {
+                (Some(a), Some(b)) => Some(a + b),
+                _ => None,
+            };
+            assert_eq!(expected, sum.eval(x));
+        }
+    }
+
+    #[test]
+    fn combine_unbounded() {
+        let a = PartialFunction::new()
+            .with_unbounded_lower(0.0, Box::new(|x| 1.0))
+            .with(0.0, 1.0, Box::new(|x| 2.0))
+            .with_unbounded_upper(1.0, Box::new(|x| 3.0))
+            .build();
+        let b = PartialFunction::new()
+            .with_unbounded_lower(0.5, Box::new(|x| 10.0))
+            .with(0.5, 2.0, Box::new(|x| 20.0))
+            .build();
+        let product = a.combine(&b, |a, b| a * b);
+        assert_eq!(Some(10.0), product.eval(-5.0));
+        assert_eq!(Some(20.0), product.eval(0.25));
+        assert_eq!(Some(40.0), product.eval(0.5));
+        assert_eq!(Some(60.0), product.eval(2.0));
+        assert_eq!(None, product.eval(2.5));
+    }
+
+    #[test]
+    fn retain_segments() {
+        let mut p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| 0.0))
+            .with(1.0, 2.0, Box::new(|x| 1.0))
+            .with(2.0, 3.0, Box::new(|x| 2.0))
+            .with(3.0, 4.0, Box::new(|x| 3.0))
+            .build();
+        p.retain_segments(|lower, higher| *lower != 1.0 && *higher != 4.0);
+        assert_eq!(2, p.len());
+        assert_eq!(Some(0.0), p.eval(0.5));
+        assert_eq!(None, p.eval(1.0));
+        assert_eq!(None, p.eval(1.5));
+        assert_eq!(Some(2.0), p.eval(2.0));
+        // The new last segment includes its higher bound.
+        assert_eq!(Some(2.0), p.eval(3.0));
+        assert_eq!(None, p.eval(3.5));
+        assert_eq!(vec![0.0, 1.0, 2.0, 3.0], p.breakpoints().to_vec());
+    }
+
+    #[test]
+    fn drain_before() {
+        let mut p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| 0.0))
+            .with(1.0, 2.0, Box::new(|x| 1.0))
+            .with_point(2.0, Box::new(|x| 2.0))
+            .with(3.0, 4.0, Box::new(|x| 3.0))
+            .build();
+        p.drain_before(&2.0);
+        assert_eq!(None, p.eval(0.5));
+        assert_eq!(None, p.eval(1.5));
+        assert_eq!(Some(2.0), p.eval(2.0));
+        assert_eq!(Some(3.0), p.eval(3.5));
+        p.drain_before(&10.0);
+        assert!(p.is_empty());
+        assert_eq!(None, p.eval(3.5));
+    }
+
+    #[test]
+    fn lower_retain_segments() {
+        let mut p = LowerPartialFunction::new()
+            .with(0, Box::new(|x| 0))
+            .with(10, Box::new(|x| 10))
+            .with(20, Box::new(|x| 20))
+            .build();
+        p.retain_segments(|lower| *lower != 10);
+        assert_eq!(2, p.len());
+        assert_eq!(Some(0), p.eval(15));
+        assert_eq!(Some(20), p.eval(25));
+        p.retain_segments(|lower| *lower > 0);
+        assert_eq!(None, p.eval(15));
+    }
+
+    #[test]
+    fn eval_into() {
+        let p = PartialFunction::new()
+            .with(0.0, 10.0, Box::new(|x| x * 0.5))
+            .build();
+        let level: i32 = 3;
+        assert_eq!(Some(1.5), p.eval_into(level));
+        assert_eq!(Some(2.0), p.eval_into(4u8));
+        assert_eq!(None, p.eval_into(11));
+        let lower = LowerPartialFunction::new()
+            .with(0.0, Box::new(|x| x))
+            .build();
+        assert_eq!(Some(7.0), lower.eval_into(7));
+        assert_eq!(None, lower.eval_into(-7));
+    }
+
+    #[test]
+    fn into_builder() {
+        let p = PartialFunction::new()
+            .with(2.0, 3.0, Box::new(|x| x * 3.0))
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with_unbounded_upper(5.0, Box::new(|x| -1.0))
+            .build();
+        let xs = (-4..40).map(|i| i as f64 * 0.25).collect::<Vec<_>>();
+        let before = xs.iter().map(|&x| p.eval(x)).collect::<Vec<_>>();
+        let builder = p.into_builder();
+        assert!(!builder.can_insert(&0.5, &1.5));
+        assert!(builder.can_insert(&1.0, &2.0));
+        let roundtrip = builder.build();
+        assert_eq!(
+            before,
+            xs.iter().map(|&x| roundtrip.eval(x)).collect::<Vec<_>>()
+        );
+        let extended = roundtrip
+            .into_builder()
+            .with(1.0, 2.0, Box::new(|x| x * 2.0))
+            .build();
+        assert_eq!(Some(3.0), extended.eval(1.5));
+        assert_eq!(Some(0.5), extended.eval(0.5));
+    }
+
+    #[test]
+    fn into_builder_layered() {
+        let p = PartialFunction::new()
+            .allow_overlaps()
+            .with(0.0, 10.0, Box::new(|x| 0.0))
+            .with(2.0, 4.0, Box::new(|x| 1.0))
+            .with_priority(3.0, 5.0, -1, Box::new(|x| 2.0))
+            .build();
+        let xs = (0..=40).map(|i| i as f64 * 0.25).collect::<Vec<_>>();
+        let before = xs.iter().map(|&x| p.eval(x)).collect::<Vec<_>>();
+        let roundtrip = p.into_builder().build();
+        assert_eq!(
+            before,
+            xs.iter().map(|&x| roundtrip.eval(x)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn lower_into_builder() {
+        let p = LowerPartialFunction::new()
+            .with(10, Box::new(|x| 10))
+            .with(0, Box::new(|x| 0))
+            .with_priority(0, 1, Box::new(|x| 1))
+            .build();
+        let builder = p.into_builder();
+        assert!(!builder.can_insert(&0));
+        let p = builder.with(5, Box::new(|x| 5)).build();
+        assert_eq!(Some(1), p.eval(0));
+        assert_eq!(Some(5), p.eval(7));
+        assert_eq!(Some(10), p.eval(10));
+        assert_eq!(3, p.len());
+    }
+
+    #[test]
+    #[should_panic(expected = "segment [2.0, 1.0) has inverted bounds")]
+    fn inverted_bounds_with() {
+        PartialFunction::new().with(2.0, 1.0, Box::new(|x| x));
+    }
+
+    #[test]
+    #[should_panic(expected = "has inverted bounds")]
+    fn inverted_bounds_with_overlaps() {
+        PartialFunction::new()
+            .allow_overlaps()
+            .with(2.0, 1.0, Box::new(|x| x));
+    }
+
+    #[test]
+    fn equal_bounds_point() {
+        let builder = PartialFunction::new().with(0.0, 1.0, Box::new(|x| x));
+        assert!(builder.can_insert(&2.0, &2.0));
+        let p = builder.with(2.0, 2.0, Box::new(|x| 5.0)).build();
+        assert_eq!(Some(5.0), p.eval(2.0));
+        assert_eq!(None, p.eval(1.5));
+        assert_eq!(None, p.eval(2.5));
+    }
+
+    #[test]
+    fn build_strict() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| x * 2.0))
+            .with_point(3.0, Box::new(|x| 5.0))
+            .build_strict();
+        assert_eq!(Some(0.0), p.eval(0.0));
+        assert_eq!(Some(2.0), p.eval(1.0));
+        assert_eq!(None, p.eval(2.0));
+        assert_eq!(Some(5.0), p.eval(3.0));
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .build_strict();
+        assert_eq!(Some(0.5), p.eval(0.5));
+        assert_eq!(None, p.eval(1.0));
+        let default = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .build();
+        assert_eq!(Some(1.0), default.eval(1.0));
+    }
+
+    #[test]
+    fn strict_eval_clamped() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x + 1.0))
+            .build_strict();
+        assert_eq!(Some(2.0), p.eval_clamped(5.0));
+        assert_eq!(Some(1.0), p.eval_clamped(-5.0));
+        assert_eq!(Some(1.0), p.mirror(0.0).eval_clamped(5.0));
+    }
+
+    #[test]
+    fn strict_try_eval() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .build_strict();
+        assert_eq!(Ok(0.0), p.try_eval(0.0));
+        assert_eq!(Err(EvalError::AboveDomain { max: 1.0 }), p.try_eval(1.0));
+        let mirrored = p.mirror(0.0);
+        assert_eq!(Ok(0.0), mirrored.try_eval(0.0));
+        assert_eq!(
+            Err(EvalError::BelowDomain { min: -1.0 }),
+            mirrored.try_eval(-1.0)
+        );
+    }
+
+    #[test]
+    fn strict_mirror() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| x * 2.0))
+            .build_strict()
+            .mirror(0.0);
+        assert_eq!(Some(0.0), p.eval(0.0));
+        assert_eq!(Some(0.5), p.eval(-0.5));
+        assert_eq!(Some(3.0), p.eval(-1.5));
+        assert_eq!(None, p.eval(-2.0));
+        // Mirroring back gives the original strict function.
+        let back = p.mirror(0.0);
+        assert_eq!(Some(0.0), back.eval(0.0));
+        assert_eq!(Some(3.0), back.eval(1.5));
+        assert_eq!(None, back.eval(2.0));
+        let scaled = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .build_strict()
+            .scale_domain(-2.0)
+            .unwrap();
+        assert_eq!(Some(0.0), scaled.eval(0.0));
+        assert_eq!(Some(0.5), scaled.eval(-1.0));
+        assert_eq!(None, scaled.eval(-2.0));
+    }
+
+    #[test]
+    fn strict_restrict() {
+        let p = PartialFunction::new()
+            .with(0.0, 2.0, Box::new(|x| x))
+            .build_strict();
+        let cut = p.restrict(0.0, 1.0);
+        assert_eq!(Some(1.0), cut.eval(1.0));
+        let p = PartialFunction::new()
+            .with(0.0, 2.0, Box::new(|x| x))
+            .build_strict();
+        let wide = p.restrict(-1.0, 3.0);
+        assert_eq!(Some(1.0), wide.eval(1.0));
+        assert_eq!(None, wide.eval(2.0));
+        let mirrored = || {
+            PartialFunction::new()
+                .with(0.0, 2.0, Box::new(|x| x))
+                .build_strict()
+                .mirror(0.0)
+        };
+        let cut = mirrored().restrict(-3.0, -1.0);
+        assert_eq!(None, cut.eval(-2.0));
+        assert_eq!(Some(1.0), cut.eval(-1.0));
+        assert!(mirrored().restrict(-3.0, -2.0).is_empty());
+    }
+
+    #[test]
+    fn strict_combine() {
+        let strict = || {
+            PartialFunction::new()
+                .with(0.0, 1.0, Box::new(|x| x))
+                .build_strict()
+        };
+        let other = PartialFunction::new()
+            .with(0.0, 2.0, Box::new(|x| 1.0))
+            .build();
+        let first = strict();
+        let sum = first.combine(&other, |a, b| a + b);
+        assert_eq!(Some(1.5), sum.eval(0.5));
+        assert_eq!(None, sum.eval(1.0));
+        let mirrored = strict().mirror(1.0);
+        let sum = mirrored.combine(&other, |a, b| a + b);
+        assert_eq!(None, sum.eval(1.0));
+        assert_eq!(Some(1.5), sum.eval(1.5));
+        assert_eq!(Some(1.0), sum.eval(2.0));
+    }
+
+    #[test]
+    fn builder_split_at() {
+        let mut builder = PartialFunction::new()
+            .with(0.0, 2.0, Box::new(|x| x * 2.0))
+            .with(2.0, 3.0, Box::new(|x| -x));
+        assert!(builder.split_at(1.0));
+        assert!(!builder.split_at(1.0));
+        assert!(!builder.split_at(2.0));
+        assert!(!builder.split_at(5.0));
+        assert!(builder.can_insert(&3.0, &4.0));
+        let p = builder.build();
+        assert_eq!(3, p.len());
+        assert_eq!(Some(1.0), p.eval(0.5));
+        assert_eq!(Some(2.0), p.eval(1.0));
+        assert_eq!(Some(3.0), p.eval(1.5));
+        assert_eq!(Some(-2.0), p.eval(2.0));
+        assert_eq!(
+            vec![(0.0, 1.0), (1.0, 2.0), (2.0, 3.0)],
+            p.into_iter()
+                .map(|s| (s.lower, s.higher))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn defined_count() {
+        let p = PartialFunction::new()
+            .with(0, 5, Box::new(|x| x))
+            .with(5, 8, Box::new(|x| x))
+            .with_point(10, Box::new(|x| x))
+            .with(12, 15, Box::new(|x| x))
+            .build();
+        let naive = |p: &PartialFunction<i32, i32>, a: i32, b: i32| {
+            (a..=b).filter(|&x| p.eval(x).is_some()).count()
+        };
+        assert_eq!(13, p.defined_count(-5, 20));
+        assert_eq!(0, p.defined_count(16, 20));
+        assert_eq!(1, p.defined_count(15, 15));
+        assert_eq!(0, p.defined_count(3, 2));
+        for a in -2..17 {
+            for b in a..17 {
+                assert_eq!(naive(&p, a, b), p.defined_count(a, b));
+            }
+        }
+        let unbounded = PartialFunction::new()
+            .with_unbounded_lower(0, Box::new(|x| x))
+            .with(2, 4, Box::new(|x| x))
+            .with_unbounded_upper(6, Box::new(|x| x))
+            .build();
+        assert_eq!(naive(&unbounded, -10, 10), unbounded.defined_count(-10, 10));
+        assert_eq!(i32::MAX as usize + 1, unbounded.defined_count(i32::MIN, -1));
+        let layered = PartialFunction::new()
+            .allow_overlaps()
+            .with(0, 10, Box::new(|x| x))
+            .with(3, 12, Box::new(|x| x))
+            .with(5, 6, Box::new(|x| x))
+            .build();
+        assert_eq!(naive(&layered, -1, 15), layered.defined_count(-1, 15));
+    }
+
+    #[test]
+    fn map_domain() {
+        // Not Clone, consumed by the conversion.
+        struct Beats(f64);
+        let bpm = 120.0;
+        let curve = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|seconds| seconds * 10.0))
+            .with(1.0, 2.0, Box::new(|seconds| 100.0))
+            .build()
+            .map_domain(move |beats: Beats| beats.0 * 60.0 / bpm);
+        assert_eq!(Some(5.0), curve.eval(Beats(1.0)));
+        assert_eq!(Some(100.0), curve.eval(Beats(3.0)));
+        assert_eq!(None, curve.eval(Beats(5.0)));
+        let lower = LowerPartialFunction::new()
+            .with(0.0, Box::new(|x| x))
+            .build()
+            .map_domain(|x: i32| f64::from(x) - 10.0);
+        assert_eq!(None, lower.eval(5));
+        assert_eq!(Some(2.0), lower.eval(12));
+    }
+
+    #[test]
+    fn eval_with_next() {
+        let p = PartialFunction::new()
+            .with_unbounded_lower(0.0, Box::new(|x| -1.0))
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(2.0, 3.0, Box::new(|x| x * 10.0))
+            .build();
+        assert_eq!(Some((-1.0, Some(0.0))), p.eval_with_next(-5.0));
+        assert_eq!(Some((0.5, Some(20.0))), p.eval_with_next(0.5));
+        assert_eq!(Some((25.0, None)), p.eval_with_next(2.5));
+        assert_eq!(None, p.eval_with_next(1.5));
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with_unbounded_upper(1.0, Box::new(|x| 7.0))
+            .build();
+        assert_eq!(Some((0.25, Some(7.0))), p.eval_with_next(0.25));
+        assert_eq!(Some((7.0, None)), p.eval_with_next(4.0));
+    }
 }
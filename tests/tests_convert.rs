@@ -0,0 +1,92 @@
+extern crate partial_function;
+
+#[cfg(test)]
+#[allow(unused_variables)]
+mod tests {
+    use partial_function::*;
+
+    #[test]
+    fn lower_roundtrip() {
+        let bounded = LowerPartialFunction::new()
+            .with(0.0, Box::new(|x| x))
+            .with(1.0, Box::new(|x| x * 2.0))
+            .with(2.0, Box::new(|x| x * 3.0))
+            .build()
+            .into_bounded(3.0);
+        assert_eq!(vec![0.0, 1.0, 2.0, 3.0], bounded.breakpoints());
+        assert_eq!(Some(9.0), bounded.eval(3.0));
+        assert_eq!(None, bounded.eval(3.5));
+        let lower = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| x * 2.0))
+            .with(2.0, 3.0, Box::new(|x| x * 3.0))
+            .build()
+            .into_lower()
+            .unwrap();
+        let roundtrip = lower.into_bounded(3.0);
+        for i in 0..=30 {
+            let x = i as f64 * 0.1;
+            assert_eq!(bounded.eval(x), roundtrip.eval(x));
+        }
+    }
+
+    #[test]
+    fn into_lower_drops_upper() {
+        let lower = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| 1))
+            .build()
+            .into_lower()
+            .unwrap();
+        assert_eq!(None, lower.eval(-0.5));
+        assert_eq!(Some(1), lower.eval(100.0));
+    }
+
+    #[test]
+    fn into_lower_gap() {
+        let error = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(2.0, 3.0, Box::new(|x| x))
+            .build()
+            .into_lower()
+            .err();
+        assert_eq!(Some(ConversionError::Gap { segment: 0 }), error);
+    }
+
+    #[test]
+    fn into_fn() {
+        let build = || {
+            PartialFunction::new()
+                .with(0.0, 1.0, Box::new(|x: f32| x))
+                .with(2.0, 3.0, Box::new(|x: f32| x * 2.0))
+                .build()
+        };
+        let reference = build();
+        let f = build().into_fn();
+        let boxed: Box<dyn Fn(f32) -> Option<f32>> = build().into();
+        let total = build().into_total_fn(-1.0);
+        for x in [-1.0, 0.0, 0.5, 1.0, 1.5, 2.0, 3.0, 4.0, f32::NAN].iter() {
+            assert_eq!(reference.eval(*x), f(*x));
+            assert_eq!(reference.eval(*x), boxed(*x));
+            assert_eq!(reference.eval(*x).unwrap_or(-1.0), total(*x));
+        }
+    }
+
+    #[test]
+    fn lower_into_fn() {
+        let build = || {
+            LowerPartialFunction::new()
+                .with(0, Box::new(|x: i32| x))
+                .with(10, Box::new(|x: i32| -x))
+                .build()
+        };
+        let reference = build();
+        let f = build().into_fn();
+        let boxed: Box<dyn Fn(i32) -> Option<i32>> = build().into();
+        let total = build().into_total_fn(0);
+        for x in [-5, 0, 5, 10, 15].iter() {
+            assert_eq!(reference.eval(*x), f(*x));
+            assert_eq!(reference.eval(*x), boxed(*x));
+            assert_eq!(reference.eval(*x).unwrap_or(0), total(*x));
+        }
+    }
+}
@@ -0,0 +1,128 @@
+extern crate partial_function;
+
+#[cfg(test)]
+#[allow(unused_variables)]
+mod tests {
+    use partial_function::*;
+    #[test]
+    fn linear_points() {
+        let p = PartialFunction::linear(&[(0.0, 0.0), (1.0, 2.0), (3.0, 0.0)]);
+        assert_eq!(Some(1.0), p.eval(0.5));
+        assert_eq!(Some(2.0), p.eval(1.0));
+        assert_eq!(Some(1.0), p.eval(2.0));
+        assert_eq!(Some(0.0), p.eval(3.0));
+        assert!(p.eval(3.5).is_none());
+    }
+    #[test]
+    fn linear_roundtrip_inverse() {
+        let p = PartialFunction::linear(&[(0.0, 10.0), (2.0, 20.0), (4.0, 60.0)]);
+        let inv = p.invert_linear().unwrap();
+        assert_eq!(Some(1.0), inv.eval(15.0));
+        assert_eq!(Some(3.0), inv.eval(40.0));
+    }
+    #[test]
+    fn invert_increasing() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x * 2.0))
+            .with(1.0, 2.0, Box::new(|x| x * 4.0 - 2.0))
+            .build();
+        let inv = p.invert_linear().unwrap();
+        assert_eq!(Some(0.5), inv.eval(1.0));
+        assert_eq!(Some(1.5), inv.eval(4.0));
+        assert_eq!(Some(2.0), inv.eval(6.0));
+    }
+    #[test]
+    fn invert_decreasing() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| 1.0 - x))
+            .build();
+        let inv = p.invert_linear().unwrap();
+        assert_eq!(Some(0.25), inv.eval(0.75));
+    }
+    #[test]
+    fn invert_non_monotonic() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| 2.0 - x))
+            .build();
+        assert_eq!(
+            Err(NonMonotonicError { segment: 1 }),
+            p.invert_linear().map(|_| ())
+        );
+    }
+
+    #[test]
+    fn antiderivative_constant() {
+        let p = PartialFunction::new()
+            .with(0.0, 5.0, Box::new(|x| 2.0))
+            .build();
+        let integral = p.antiderivative(4);
+        assert_eq!(Some(0.0), integral.eval(0.0));
+        assert_eq!(Some(5.0), integral.eval(2.5));
+        assert_eq!(Some(10.0), integral.eval(5.0));
+        assert_eq!(None, integral.eval(6.0));
+    }
+
+    #[test]
+    fn ascii_plot() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(2.0, 3.0, Box::new(|x| 1.0))
+            .build();
+        let plot = p.ascii_plot(4, 2, 0.0, 3.0);
+        let lines = plot.lines().collect::<Vec<_>>();
+        assert_eq!(4, lines.len());
+        assert_eq!("      1.00 |  **", lines[0]);
+        assert_eq!("      0.00 |*   ", lines[1]);
+        assert_eq!("           +----", lines[2]);
+        assert_eq!("           0.00 3.00", lines[3]);
+    }
+
+    #[test]
+    fn ascii_plot_empty() {
+        let p = PartialFunction::linear(&[(0.0, 0.0), (1.0, 1.0)]);
+        assert_eq!("", p.ascii_plot(0, 5, 0.0, 1.0));
+        assert!(p.ascii_plot(5, 3, 5.0, 6.0).chars().all(|c| c != '*'));
+    }
+
+    #[test]
+    fn from_fn_midpoints() {
+        let calls = std::cell::Cell::new(0);
+        let p = PartialFunction::from_fn(0.0, 2.0, 4, |x| {
+            calls.set(calls.get() + 1);
+            x * x
+        });
+        assert_eq!(4, calls.get());
+        assert_eq!(4, p.len());
+        assert_eq!(None, p.eval(-0.1));
+        assert_eq!(Some(0.0625), p.eval(0.0));
+        assert_eq!(Some(0.5625), p.eval(0.5));
+        assert_eq!(Some(3.0625), p.eval(2.0));
+        assert_eq!(None, p.eval(2.1));
+        assert!(PartialFunction::from_fn(0.0, 1.0, 0, |x| x).is_empty());
+    }
+
+    #[test]
+    fn antiderivative_layered() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| 1.0))
+            .with_priority(0.5, 0.75, 1, Box::new(|x| 3.0))
+            .build();
+        let integral = p.antiderivative(1);
+        assert_eq!(Some(0.5), integral.eval(0.5));
+        assert_eq!(Some(1.25), integral.eval(0.75));
+        assert_eq!(Some(1.5), integral.eval(1.0));
+    }
+
+    #[test]
+    fn invert_linear_layered() {
+        let p = PartialFunction::new()
+            .with(0.0, 2.0, Box::new(|x| x))
+            .with_priority(1.0, 2.0, 1, Box::new(|x| 2.0 * x - 1.0))
+            .build();
+        let inv = p.invert_linear().unwrap();
+        assert_eq!(Some(0.5), inv.eval(0.5));
+        assert_eq!(Some(1.5), inv.eval(2.0));
+        assert_eq!(Some(2.0), inv.eval(3.0));
+    }
+}
@@ -127,4 +127,97 @@ mod tests {
             [0.0]: x -> 2,
         };
     }
+
+    #[test]
+    fn upper_partial_normal() {
+        let f = uppartfn! {
+            [1.0]: x -> 2,
+            [0.0]: x -> 1,
+        };
+        assert_eq!(f.eval(-1000.0), Some(1));
+        assert_eq!(f.eval(0.0), Some(1));
+        assert_eq!(f.eval(0.5), Some(2));
+        assert_eq!(f.eval(1.0), Some(2));
+        assert_eq!(f.eval(1.5), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn upper_partial_overlap() {
+        let f = uppartfn! {
+            [0.0]: x -> 1,
+            [0.0]: x -> 2,
+        };
+    }
+
+    #[test]
+    fn discrete_normal() {
+        let f = discretefn! {
+            ["double"]: x -> 2,
+            ["triple"]: x -> 3,
+        };
+        assert_eq!(f.eval("double"), Some(2));
+        assert_eq!(f.eval("triple"), Some(3));
+        assert_eq!(f.eval("quadruple"), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn discrete_overlap() {
+        let f = discretefn! {
+            [1]: x -> x,
+            [1]: x -> x * 2,
+        };
+    }
+
+    #[test]
+    fn discrete_can_insert() {
+        let builder = DiscretePartialFunction::new().with('a', Box::new(|c: char| c as u32));
+        assert!(!builder.can_insert(&'a'));
+        assert!(builder.can_insert(&'b'));
+        assert_eq!(Some(97), builder.build().eval('a'));
+    }
+
+    fn make_curve(step: f32) -> PartialFunction<'static, f32, f32> {
+        let weights: Vec<f32> = (1..=3).map(|i| i as f32).collect();
+        let scale = 10.0;
+        let start = 0.0;
+        partfn! {
+            [start, start + step]: move x -> x * weights[0],
+            [start + step, start + 2.0 * step]: move x -> x * scale,
+        }
+    }
+
+    #[test]
+    fn move_captures() {
+        let p = make_curve(1.0);
+        assert_eq!(Some(0.5), p.eval(0.5));
+        assert_eq!(Some(15.0), p.eval(1.5));
+        let thresholds: Vec<i32> = (1..=2).map(|i| i * 10).collect();
+        let bonus = 5;
+        let f = lowpartfn! {
+            [0]: x -> x + bonus,
+            [thresholds[0]]: move x -> x + thresholds.len() as i32,
+        };
+        assert_eq!(Some(6), f.eval(1));
+        assert_eq!(Some(12), f.eval(10));
+    }
+
+    #[test]
+    fn bounds_evaluated_once_in_order() {
+        let calls = std::cell::RefCell::new(vec![]);
+        let bound = |i: i32, step: i32| {
+            calls.borrow_mut().push(i);
+            i * step
+        };
+        let step = 2;
+        let p = partfn! {
+            [bound(0, step), bound(1, step)]: x -> x,
+            [bound(1, step), bound(2, step)]: x -> -x,
+        };
+        assert_eq!(vec![0, 1, 1, 2], *calls.borrow());
+        assert_eq!(Some(1), p.eval(1));
+        assert_eq!(Some(-3), p.eval(3));
+        assert_eq!(4, calls.borrow().len());
+    }
 }
@@ -0,0 +1,88 @@
+#![cfg(feature = "memoize")]
+extern crate partial_function;
+
+#[cfg(test)]
+mod tests {
+    use partial_function::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn memoized() {
+        let calls = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&calls);
+        let mut p = PartialFunction::new()
+            .with(
+                0,
+                10,
+                Box::new(move |x| {
+                    counter.set(counter.get() + 1);
+                    x * 2
+                }),
+            )
+            .build()
+            .memoized();
+        assert_eq!(Some(6), p.eval(3));
+        assert_eq!(Some(6), p.eval(3));
+        assert_eq!(None, p.eval(20));
+        assert_eq!(1, calls.get());
+        p.clear_cache();
+        assert_eq!(Some(6), p.eval(3));
+        assert_eq!(2, calls.get());
+    }
+
+    #[test]
+    fn memoized_max_size() {
+        let calls = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&calls);
+        let p = PartialFunction::new()
+            .with(
+                0,
+                10,
+                Box::new(move |x| {
+                    counter.set(counter.get() + 1);
+                    x
+                }),
+            )
+            .build()
+            .memoized_with_capacity(2)
+            .with_max_size(2);
+        p.eval(1);
+        p.eval(2);
+        p.eval(3);
+        assert_eq!(2, p.cache_len());
+        p.eval(3);
+        assert_eq!(3, calls.get());
+        p.eval(1);
+        assert_eq!(4, calls.get());
+    }
+
+    #[test]
+    fn memoized_max_size_keeps_newest() {
+        let calls = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&calls);
+        let p = PartialFunction::new()
+            .with(
+                0,
+                10,
+                Box::new(move |x| {
+                    counter.set(counter.get() + 1);
+                    x
+                }),
+            )
+            .build()
+            .memoized();
+        p.eval(1);
+        p.eval(2);
+        p.eval(3);
+        let p = p.with_max_size(2);
+        assert_eq!(2, p.cache_len());
+        p.eval(2);
+        p.eval(3);
+        assert_eq!(3, calls.get());
+        p.eval(1);
+        assert_eq!(4, calls.get());
+        let p = p.with_max_size(5);
+        assert_eq!(2, p.cache_len());
+    }
+}
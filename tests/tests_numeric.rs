@@ -0,0 +1,194 @@
+extern crate partial_function;
+
+#[cfg(test)]
+mod tests {
+    use partial_function::*;
+    #[test]
+    fn derivative_smooth_linear() {
+        let p = PartialFunction::new()
+            .with(0.0, 10.0, Box::new(|x| 3.0 * x + 1.0))
+            .build();
+        let d = p.derivative_smooth(5.0, 0.5, 9).unwrap();
+        assert!((d - 3.0f64).abs() < 1e-9);
+    }
+    #[test]
+    fn derivative_smooth_edge() {
+        let p = PartialFunction::new()
+            .with(0.0f32, 10.0, Box::new(|x| -2.0 * x))
+            .build();
+        let d = p.derivative_smooth(0.0, 1.0, 5).unwrap();
+        assert!((d + 2.0).abs() < 1e-4);
+        assert!(p.derivative_smooth(-5.0, 1.0, 5).is_none());
+    }
+
+    #[test]
+    fn find_input_bisects() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x: f64| x * x))
+            .with(1.0, 2.0, Box::new(|x| 2.0 * x - 1.0))
+            .build();
+        let x = p.find_input(&0.25, 1e-9).unwrap();
+        assert!((x - 0.5).abs() < 1e-6);
+        let x = p.find_input(&2.0, 1e-9).unwrap();
+        assert!((x - 1.5).abs() < 1e-6);
+        assert_eq!(Some(1.0), p.find_input(&1.0, 1e-9));
+        assert_eq!(Some(2.0), p.find_input(&3.0, 1e-9));
+        assert_eq!(None, p.find_input(&4.0, 1e-9));
+    }
+    #[test]
+    fn find_input_gap() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x: f64| x))
+            .with(2.0, 3.0, Box::new(|x| x))
+            .build();
+        assert_eq!(None, p.find_input(&1.0, 1e-9));
+        assert_eq!(None, p.find_input(&1.5, 1e-9));
+        assert_eq!(Some(2.0), p.find_input(&2.0, 1e-9));
+    }
+    #[test]
+    fn find_input_strict() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x: f64| x))
+            .build_strict();
+        assert_eq!(None, p.find_input(&1.0, 1e-9));
+        assert_eq!(Some(0.0), p.find_input(&0.0, 1e-9));
+        let mirrored = p.mirror(0.0);
+        assert_eq!(None, mirrored.find_input(&1.0, 1e-9));
+        assert_eq!(Some(0.0), mirrored.find_input(&0.0, 1e-9));
+    }
+    #[test]
+    fn find_input_layered() {
+        let p = PartialFunction::new()
+            .with(0.0, 2.0, Box::new(|x: f64| x))
+            .with_priority(1.0, 2.0, 1, Box::new(|x| 2.0 * x - 1.0))
+            .build();
+        let x = p.find_input(&1.5, 1e-9).unwrap();
+        assert!((x - 1.25).abs() < 1e-6);
+        assert_eq!(Some(2.0), p.find_input(&3.0, 1e-9));
+        assert_eq!(None, p.find_input(&4.0, 1e-9));
+    }
+
+    #[test]
+    fn periodic() {
+        let p = PartialFunction::new()
+            .with(0.0, 0.5, Box::new(|x| x))
+            .with(0.5, 1.0, Box::new(|x| 1.0 - x))
+            .build()
+            .periodic(1.0);
+        assert_eq!(Some(0.25), p.eval(2.25));
+        assert_eq!(Some(0.25), p.eval(-0.25));
+        assert_eq!(Some(0.0), p.eval(-1.0));
+    }
+
+    #[test]
+    fn periodic_domain() {
+        let p = PartialFunction::new()
+            .with_const(2.0, 3.0, 1)
+            .with_const(3.0, 6.0, 2)
+            .build()
+            .periodic_domain()
+            .unwrap();
+        assert_eq!(Some(1), p.eval(2.0));
+        assert_eq!(Some(2), p.eval(-0.5));
+        assert_eq!(Some(1), p.eval(-1.5));
+        let gapped = PartialFunction::new()
+            .with_const(0.0, 1.0, 1)
+            .with_const(2.0, 3.0, 2)
+            .build();
+        assert!(gapped.periodic_domain().is_none());
+    }
+
+    #[test]
+    fn approx_eq() {
+        let line = PartialFunction::linear(&[(0.0, 0.0), (2.0, 2.0)]);
+        let split = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x + 1e-9))
+            .with(1.0, 2.0, Box::new(|x| x))
+            .build();
+        assert!(line.approx_eq(&split, 50, 1e-6));
+        assert!(!line.approx_eq(&split, 50, 1e-12));
+        let longer = PartialFunction::linear(&[(0.0, 0.0), (3.0, 3.0)]);
+        assert!(!line.approx_eq(&longer, 50, 1e-6));
+        assert!(!line.approx_eq(&PartialFunction::new().build(), 50, 1e-6));
+    }
+
+    #[test]
+    fn integrate() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|_| 2.0))
+            .with(1.0, 3.0, Box::new(|x| x))
+            .with(4.0, 5.0, Box::new(|_| 1.0))
+            .build();
+        assert_eq!(Some(2.0), p.integrate(0.0, 1.0, 1));
+        assert_eq!(Some(6.0), p.integrate(0.0, 3.0, 4));
+        assert_eq!(Some(-6.0), p.integrate(3.0, 0.0, 4));
+        assert_eq!(Some(1.625), p.integrate(0.5, 1.5, 1));
+        assert_eq!(Some(0.0), p.integrate(2.0, 2.0, 1));
+        assert_eq!(None, p.integrate(2.0, 4.5, 10));
+        assert_eq!(None, p.integrate(-1.0, 1.0, 10));
+        let upper = PartialFunction::new()
+            .with_unbounded_upper(0.0, Box::new(|_: f32| 1.0f32))
+            .build();
+        assert_eq!(Some(10.0), upper.integrate(0.0, 10.0, 1));
+    }
+
+    #[test]
+    fn min_max_output() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| 5.0 - x))
+            .with(3.0, 4.0, Box::new(|x| -x))
+            .build();
+        assert_eq!(Some(4.0), p.max_output(0.0, 2.0, 1));
+        assert_eq!(Some(0.0), p.min_output(0.0, 2.0, 1));
+        assert_eq!(Some(1.0), p.max_output(0.0, 0.99, 1).map(f64::ceil));
+        assert_eq!(Some(-4.0), p.min_output(4.0, 0.0, 2));
+        assert_eq!(Some(0.5), p.max_output(0.5, 0.5, 2));
+        assert_eq!(None, p.max_output(2.2, 2.8, 4));
+        assert_eq!(None, p.min_output(5.0, 6.0, 4));
+    }
+
+    #[test]
+    fn min_max_output_step() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x * 10.0))
+            .with(1.0, 2.0, Box::new(|x| x - 1.0))
+            .with(2.0, 3.0, Box::new(|x| -x * 10.0))
+            .with(3.0, 4.0, Box::new(|_| 0.0))
+            .build();
+        // 10 and -30 are only approached before excluded higher bounds.
+        assert_eq!(Some(5.0), p.max_output(0.0, 2.0, 2));
+        assert_eq!(Some(-25.0), p.min_output(2.0, 4.0, 2));
+        assert_eq!(Some(0.0), p.max_output(1.0, 2.0, 1));
+    }
+
+    #[test]
+    fn derivative_at() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x * x))
+            .with(1.0, 2.0, Box::new(|x| 10.0 - x))
+            .with(3.0, 3.0, Box::new(|x| x))
+            .build();
+        let close = |a: Option<f64>, b: f64| (a.unwrap() - b).abs() < 1e-6;
+        assert!(close(p.derivative_at(0.5, 0.01), 1.0));
+        assert!(close(p.derivative_at(0.0, 0.01), 0.01));
+        assert!(close(p.derivative_at(1.0, 0.01), -1.0));
+        assert!(close(p.derivative_at(0.995, 0.01), 1.985));
+        assert_eq!(None, p.derivative_at(3.0, 0.01));
+        assert_eq!(None, p.derivative_at(2.5, 0.01));
+    }
+
+    #[test]
+    fn window_average() {
+        let p = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|_| 1.0))
+            .with(1.0, 2.0, Box::new(|_| 3.0))
+            .build();
+        assert_eq!(Some(2.0), p.window_average(1.0, 0.75, 4));
+        // Only the samples at 1.5 and 2.0 are defined.
+        assert_eq!(Some(3.0), p.window_average(2.0, 0.5, 3));
+        assert_eq!(Some(1.0), p.window_average(0.5, 0.5, 1));
+        assert_eq!(None, p.window_average(5.0, 1.0, 5));
+        assert_eq!(None, p.window_average(0.5, 1.0, 0));
+    }
+}
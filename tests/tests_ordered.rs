@@ -0,0 +1,67 @@
+extern crate partial_function;
+
+#[cfg(test)]
+#[allow(unused_variables)]
+mod tests {
+    use partial_function::*;
+
+    #[test]
+    fn ordered_eval() {
+        let p = OrderedPartialFunction::new()
+            .with(10, 20, Box::new(|x| x * 2))
+            .with(0, 10, Box::new(|x| x))
+            .with(30, 40, Box::new(|x| -x))
+            .with(20, 20, Box::new(|x| 0))
+            .build();
+        assert_eq!(None, p.eval(-1));
+        assert_eq!(Some(5), p.eval(5));
+        assert_eq!(Some(20), p.eval(10));
+        assert_eq!(Some(0), p.eval(20));
+        assert_eq!(None, p.eval(25));
+        assert_eq!(Some(-40), p.eval(40));
+        assert_eq!(None, p.eval(41));
+    }
+
+    #[test]
+    fn ordered_can_insert() {
+        let builder = OrderedPartialFunction::<i32, i32>::new()
+            .with(0, 10, Box::new(|x| x))
+            .with(20, 30, Box::new(|x| x));
+        assert!(builder.can_insert(&10, &20));
+        assert!(builder.can_insert(&10, &10));
+        assert!(builder.can_insert(&30, &40));
+        assert!(!builder.can_insert(&0, &0));
+        assert!(!builder.can_insert(&5, &15));
+        assert!(!builder.can_insert(&15, &25));
+        assert!(!builder.can_insert(&-5, &35));
+        assert!(!builder.can_insert(&15, &12));
+    }
+
+    #[test]
+    fn ordered_insert_remove() {
+        let mut p = OrderedPartialFunction::new()
+            .with(0, 10, Box::new(|x| x))
+            .build();
+        assert!(p.insert(10, 20, Box::new(|x| x * 2)));
+        assert!(!p.insert(15, 25, Box::new(|x| x)));
+        assert_eq!(2, p.len());
+        assert_eq!(Some(40), p.eval(20));
+        assert!(p.remove(&10));
+        assert!(!p.remove(&10));
+        assert_eq!(None, p.eval(15));
+        assert_eq!(Some(10), p.eval(10));
+    }
+
+    #[test]
+    fn ordered_insert_past_end() {
+        let mut p = OrderedPartialFunction::new()
+            .with(0, 5, Box::new(|x| x))
+            .build();
+        assert_eq!(Some(5), p.eval(5));
+        assert!(p.insert(10, 12, Box::new(|x| x)));
+        assert_eq!(None, p.eval(5));
+        assert_eq!(Some(12), p.eval(12));
+        assert!(p.insert(5, 10, Box::new(|x| -x)));
+        assert_eq!(Some(-5), p.eval(5));
+    }
+}
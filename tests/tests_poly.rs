@@ -0,0 +1,98 @@
+extern crate partial_function;
+
+#[cfg(test)]
+#[allow(unused_variables)]
+mod tests {
+    use partial_function::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn poly_matches_closures() {
+        let poly = PolyPartialFunction::new()
+            .with(PolySegment::linear(1.0, 3.0, 1.0, 5.0))
+            .with(PolySegment::constant(0.0, 1.0, 2.0))
+            .with(PolySegment::new(vec![1.0, 0.0, -1.0], 3.0, 4.0))
+            .build();
+        let closures = PartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| 2.0))
+            .with(1.0, 3.0, Box::new(|x| 1.0 + 2.0 * (x - 1.0)))
+            .with(3.0, 4.0, Box::new(|x: f64| 1.0 - (x - 3.0).powi(2)))
+            .build();
+        for i in -4..=20 {
+            let x = i as f64 * 0.25;
+            assert_eq!(closures.eval(x), poly.eval(x));
+        }
+        assert_eq!(None, poly.eval(f64::NAN));
+    }
+
+    #[test]
+    fn poly_hermite() {
+        let s = PolySegment::hermite(1.0_f64, 3.0, 2.0, 4.0, 0.5, -1.0);
+        assert_eq!(2.0, s.eval(1.0));
+        assert!((s.eval(3.0) - 4.0).abs() < 1e-12);
+        let h = 1e-6;
+        let slope = |x: f64| (s.eval(x + h) - s.eval(x - h)) / (2.0 * h);
+        assert!((slope(1.0) - 0.5).abs() < 1e-6);
+        assert!((slope(3.0) + 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn poly_data() {
+        let p = PolyPartialFunction::new()
+            .with(PolySegment::linear(0.0, 1.0, 0.0, 1.0))
+            .build();
+        let copy = p.clone();
+        assert_eq!(p, copy);
+        assert_eq!(
+            PolySegment::new(vec![0.0, 1.0], 0.0, 1.0),
+            copy.segments()[0]
+        );
+        assert!(format!("{:?}", copy).contains("coeffs"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn poly_overlap() {
+        PolyPartialFunction::new()
+            .with(PolySegment::constant(0.0, 2.0, 1.0))
+            .with(PolySegment::constant(1.0, 3.0, 1.0));
+    }
+
+    #[test]
+    fn poly_try_from() {
+        let p = PolyPartialFunction::try_from(vec![
+            PolySegment::constant(1.0, 2.0, 1.0),
+            PolySegment::constant(0.0, 1.0, 0.0),
+        ])
+        .unwrap();
+        assert_eq!(Some(0.0), p.eval(0.5));
+        assert_eq!(Some(1.0), p.eval(1.0));
+        assert_eq!(1.0, p.segments()[1].lower);
+        let overlapping = PolyPartialFunction::try_from(vec![
+            PolySegment::constant(0.0, 2.0, 0.0),
+            PolySegment::constant(1.0, 3.0, 1.0),
+        ]);
+        assert_eq!(1, overlapping.unwrap_err().errors.len());
+        let inverted = PolyPartialFunction::try_from(vec![PolySegment::constant(2.0, 1.0, 0.0)]);
+        assert!(inverted.is_err());
+        let both = PolyPartialFunction::try_from(vec![
+            PolySegment::constant(5.0, 4.0, 0.0),
+            PolySegment::constant(0.0, 2.0, 0.0),
+            PolySegment::constant(1.0, 3.0, 1.0),
+        ]);
+        assert_eq!(
+            vec![
+                InsertError::InvertedBounds {
+                    lower: 5.0,
+                    higher: 4.0
+                },
+                InsertError::Overlap(OverlapError {
+                    first: 1,
+                    second: 2,
+                    at: 1.0
+                }),
+            ],
+            both.unwrap_err().errors
+        );
+    }
+}
@@ -0,0 +1,25 @@
+#![cfg(feature = "rayon")]
+extern crate partial_function;
+
+#[cfg(test)]
+mod tests {
+    use partial_function::*;
+
+    #[test]
+    fn par_eval_all() {
+        let p = SyncPartialFunction::new()
+            .with(0.0, 1.0, Box::new(|x| x))
+            .with(1.0, 2.0, Box::new(|x| x * x))
+            .with(3.0, 5.0, Box::new(|x| -x))
+            .build();
+        // Simple linear congruential generator, to avoid depending on rand.
+        let mut state = 12345u64;
+        let xs = (0..10_000)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                (state >> 33) as f64 / (1u64 << 31) as f64 * 6.0 - 0.5
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(p.eval_all(&xs), p.par_eval_all(&xs));
+    }
+}